@@ -1,12 +1,34 @@
+use std::env;
 use std::io;
+use std::path::Path;
 
 use anyhow::Result;
 
+use nexers::compression;
 use nexers::db;
+use nexers::incremental::IndexProperties;
+use nexers::incremental::PROPERTIES_FILE;
 
+/// `build_db [some/download/dir] < index`, where the directory, by default the current one,
+/// should have the index's `.properties` file, so `update_db` can apply later chunks
 fn main() -> Result<()> {
+    let dir = env::args().nth(1).unwrap_or_else(|| ".".to_string());
+    let props = match Path::new(&dir).join(PROPERTIES_FILE).is_file() {
+        true => Some(IndexProperties::open(&dir)?),
+        false => {
+            eprintln!("no {PROPERTIES_FILE} in {dir:?}, so the db can't be updated");
+            None
+        }
+    };
+
     let conn = rusqlite::Connection::open("maven.db")?;
-    conn.execute_batch(db::SCHEMA)?;
-    db::ingest(io::stdin().lock(), conn)?;
+    db::migrate(&conn)?;
+    let (conn, stats) = db::ingest(compression::decompress(io::stdin().lock())?, conn)?;
+    eprintln!("{stats:?}");
+    if let Some(props) = &props {
+        db::set_properties(&conn, props)?;
+    }
+    db::index_checksums(&conn)?;
+    db::index_last_modified(&conn)?;
     Ok(())
 }
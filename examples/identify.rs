@@ -0,0 +1,23 @@
+use std::env;
+
+use anyhow::Result;
+
+use nexers::db;
+use nexers::identify::identify;
+
+/// `identify some/lib/dir [maven.db]`, with a db from `build_db`
+fn main() -> Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let path = args.first().map(|s| s.as_str()).unwrap_or(".");
+    let db = args.get(1).map(|s| s.as_str()).unwrap_or("maven.db");
+    let conn = rusqlite::Connection::open(db)?;
+    for found in identify(path, |checksum| db::find_by_checksum(&conn, checksum))? {
+        let matches = found
+            .matches
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        println!("{}: {}", found.path.display(), matches.join(", "));
+    }
+    Ok(())
+}
@@ -5,24 +5,23 @@ use std::io;
 use anyhow::Result;
 
 use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
 
 fn main() -> Result<()> {
     let args = env::args().skip(1).collect::<Vec<_>>();
-    let path = args.get(0).map(|s| s.as_str()).unwrap_or("sample-index");
+    let path = args.first().map(|s| s.as_str()).unwrap_or("sample-index");
     let from = io::BufReader::new(fs::File::open(path)?);
     let mut errors = 0;
-    nexers::nexus::read(from, |event| {
-        match event {
-            Event::Doc(d) => {
-                if d.id.group == "com.google.guava" && d.id.artifact == "guava" {
-                    println!("{:?} {:?}", d.id, d.object_info);
-                }
-            }
+    // only records for guava are decoded; the rest are skipped after reading `u`
+    let events = IndexReader::new(from)?
+        .filter_uniq(|id| id.group == "com.google.guava" && id.artifact == "guava");
+    for event in events {
+        match event? {
+            Event::Doc(d) => println!("{:?} {:?}", d.id, d.object_info),
             Event::Error { .. } => errors += 1,
-            Event::Delete(_) => (),
+            Event::Delete(_) | Event::Descriptor { .. } | Event::Groups { .. } => (),
         }
-        Ok(())
-    })?;
+    }
     println!("..and {errors} errors");
     Ok(())
 }
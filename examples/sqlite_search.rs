@@ -8,8 +8,8 @@ use nexers::db;
 fn main() -> Result<()> {
     let from = io::BufReader::new(fs::File::open("sample-index")?);
     let conn = rusqlite::Connection::open("search.db")?;
-    conn.execute_batch(db::SCHEMA)?;
-    let conn = db::ingest(from, conn)?;
+    db::migrate(&conn)?;
+    let (conn, _stats) = db::ingest(from, conn)?;
 
     println!(
         "{:?}",
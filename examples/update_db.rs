@@ -0,0 +1,23 @@
+use std::env;
+
+use anyhow::anyhow;
+use anyhow::Result;
+
+use nexers::db;
+use nexers::incremental;
+
+/// `update_db some/download/dir [maven.db]`, where the directory has the
+/// `.properties` file, and the chunks it lists, and the db was made by `build_db` with
+/// the `.properties` of the index it read, or has otherwise had its `last_chunk` set
+fn main() -> Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let dir = args.first().map(|s| s.as_str()).unwrap_or(".");
+    let db = args.get(1).map(|s| s.as_str()).unwrap_or("maven.db");
+    let conn = rusqlite::Connection::open(db)?;
+    let last =
+        db::last_chunk(&conn)?.ok_or_else(|| anyhow!("no last_chunk in {db:?}; see build_db"))?;
+    let chunks = incremental::pending_chunks(dir, last)?;
+    let (conn, stats) = db::update(conn, &chunks)?;
+    eprintln!("{stats:?}, now at {:?}", db::last_chunk(&conn)?);
+    Ok(())
+}
@@ -0,0 +1,28 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexers::java::DataInput;
+
+// drive `DataInput` with the first half of the data choosing the operations,
+// and the second half being read; lengths are never trusted up front
+fuzz_target!(|data: &[u8]| {
+    let (ops, input) = data.split_at(data.len() / 2);
+    let mut input = DataInput::new(input);
+
+    for op in ops {
+        let ok = match op % 6 {
+            0 => input.read_byte().is_ok(),
+            1 => input.read_unsigned_short().is_ok(),
+            2 => input.read_int().is_ok(),
+            3 => input.read_long().is_ok(),
+            4 => match input.read_int() {
+                Ok(len) => input.read_utf8(len as u32 as usize).is_ok(),
+                Err(_) => false,
+            },
+            _ => input.check_eof().is_ok(),
+        };
+        if !ok {
+            break;
+        }
+    }
+});
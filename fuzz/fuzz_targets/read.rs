@@ -0,0 +1,54 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::ReaderOptions;
+use nexers::nexus::Writer;
+
+fuzz_target!(|data: &[u8]| {
+    let options = ReaderOptions {
+        max_fields: 64,
+        max_name_len: 256,
+        max_value_len: 64 * 1024,
+        max_record_size: 256 * 1024,
+        resync: false,
+    };
+
+    let reader = match IndexReader::with_options(data, options.clone()) {
+        Ok(reader) => reader,
+        Err(_) => return,
+    };
+
+    for event in reader {
+        let doc = match event {
+            Ok(Event::Doc(doc)) => doc,
+            Ok(_) => continue,
+            Err(_) => break,
+        };
+
+        // anything we can read, we must be able to write back identically
+        let mut writer = Writer::new(Vec::new(), -1).expect("vec");
+        writer.write_doc(&doc).expect("vec");
+        let written = writer.into_inner().expect("vec");
+
+        let mut again = IndexReader::with_options(written.as_slice(), options.clone()).expect("header");
+        match again.next() {
+            Some(Ok(Event::Doc(again))) => assert_eq!(doc, again),
+            other => panic!("didn't read back {doc:?}: {other:?}"),
+        }
+    }
+
+    // resyncing must always reach the end, without giving up
+    let options = ReaderOptions {
+        resync: true,
+        ..options
+    };
+    if let Ok(reader) = IndexReader::with_options(data, options) {
+        for event in reader {
+            if let Err(e) = event {
+                panic!("resync gave up: {e}");
+            }
+        }
+    }
+});
@@ -0,0 +1,62 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+
+use anyhow::bail;
+use anyhow::Result;
+
+/// How an index is compressed, as far as we can tell from its first few bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// including anything we don't recognise
+    None,
+    /// what Central publishes
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+/// The most magic bytes any format needs.
+const MAGIC_LEN: usize = 6;
+
+impl Compression {
+    pub fn sniff(magic: &[u8]) -> Compression {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Wrap `from` in a decoder for whatever compression it's using, if any.
+///
+/// Each format needs its feature (`gzip`, `zstd` or `xz`) enabled; it's an error to
+/// find one which isn't.
+pub fn decompress<'r, R: BufRead + 'r>(mut from: R) -> Result<Box<dyn BufRead + 'r>> {
+    // `fill_buf` might not return enough to tell, e.g. from a pipe
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    (&mut from).take(MAGIC_LEN as u64).read_to_end(&mut magic)?;
+    let compression = Compression::sniff(&magic);
+    let from = io::Cursor::new(magic).chain(from);
+
+    Ok(match compression {
+        Compression::None => Box::new(from),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Box::new(io::BufReader::new(flate2::bufread::MultiGzDecoder::new(
+            from,
+        ))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Box::new(io::BufReader::new(zstd::Decoder::with_buffer(from)?)),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Box::new(io::BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(from),
+        )),
+        #[allow(unreachable_patterns)]
+        other => bail!("{other:?} input, but the feature to read it isn't enabled"),
+    })
+}
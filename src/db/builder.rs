@@ -10,9 +10,40 @@ use rusqlite::OptionalExtension;
 
 use crate::nexus::AttachmentStatus;
 use crate::nexus::Doc;
+use crate::nexus::UniqId;
 
 type Cache = (&'static str, HashMap<String, i64>);
 
+/// Databases from before the unique index can have duplicates, of which the newest is kept.
+pub(super) const DEDUPLICATE: &str = r"
+delete from versions where id in (
+  select id from (
+    select id, row_number() over (
+      partition by group_id, artifact_id, version, coalesce(classifier_id, 0), coalesce(extension_id, 0)
+      order by last_modified desc, id desc
+    ) as newness from versions
+  ) where newness > 1
+)";
+
+/// `coalesce`, as nulls are never equal.
+pub(super) const UNIQUE_INDEX: &str = r"
+create unique index if not exists versions_coordinates
+  on versions (group_id, artifact_id, version, coalesce(classifier_id, 0), coalesce(extension_id, 0))";
+
+/// What [`DbBuilder::add`] did with a doc.
+///
+/// Rows are matched on the unique key: group, artifact, version, classifier and extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Added {
+    /// no row had the doc's key
+    Inserted,
+    /// a row had the doc's key, and a `last_modified` no later than the doc's, so was overwritten
+    Replaced,
+    /// a row had the doc's key, and a later `last_modified` than the doc's, so was kept,
+    /// and the doc ignored
+    Older,
+}
+
 pub struct DbBuilder<'t> {
     conn: &'t rusqlite::Connection,
     group_cache: Cache,
@@ -24,7 +55,8 @@ pub struct DbBuilder<'t> {
 }
 
 impl<'t> DbBuilder<'t> {
-    pub fn new(conn: &rusqlite::Connection) -> Result<DbBuilder> {
+    /// For a new database, which is [`migrate`](super::migrate)d first, so may be empty.
+    pub fn new(conn: &rusqlite::Connection) -> Result<DbBuilder<'_>> {
         let mut us = DbBuilder {
             conn,
             group_cache: ("group", HashMap::with_capacity(40 * 1_024)),
@@ -35,12 +67,43 @@ impl<'t> DbBuilder<'t> {
             classifier_cache: ("classifier", HashMap::with_capacity(1_024)),
         };
 
+        super::migrate(conn)?;
         us.create_string_tables()?;
         us.write_examples()?;
 
         Ok(us)
     }
 
+    /// For a database which already has data in, e.g. from [`ingest`](super::ingest):
+    /// the caches are loaded from it, instead of being written.
+    pub fn open(conn: &rusqlite::Connection) -> Result<DbBuilder<'_>> {
+        let mut us = DbBuilder {
+            conn,
+            group_cache: ("group", HashMap::new()),
+            artifact_cache: ("artifact", HashMap::new()),
+            name_cache: ("name", HashMap::new()),
+            desc_cache: ("desc", HashMap::new()),
+            packaging_cache: ("packaging", HashMap::new()),
+            classifier_cache: ("classifier", HashMap::new()),
+        };
+
+        // `add` relies on the unique index, which the database may predate
+        super::migrate(conn)?;
+        us.create_string_tables()?;
+        for cache in [
+            &mut us.group_cache,
+            &mut us.artifact_cache,
+            &mut us.name_cache,
+            &mut us.desc_cache,
+            &mut us.packaging_cache,
+            &mut us.classifier_cache,
+        ] {
+            load_cache(conn, cache)?;
+        }
+
+        Ok(us)
+    }
+
     pub fn create_string_tables(&self) -> Result<()> {
         for (name, _cache) in &[
             &self.group_cache,
@@ -76,19 +139,13 @@ create table if not exists {name}_names (
         Ok(())
     }
 
-    pub fn add(&mut self, doc: &Doc) -> Result<()> {
+    /// Add the doc, or, if a row with its key is already there, replace it,
+    /// unless that row was modified later; see [`Added`].
+    pub fn add(&mut self, doc: &Doc) -> Result<Added> {
         let group_name = string_write(self.conn, &mut self.group_cache, &doc.id.group)?;
         let artifact_name = string_write(self.conn, &mut self.artifact_cache, &doc.id.artifact)?;
-        let name_name = option_write(
-            self.conn,
-            &mut self.name_cache,
-            doc.name.as_ref().map(|s| s.as_str()),
-        )?;
-        let desc_name = option_write(
-            self.conn,
-            &mut self.desc_cache,
-            doc.description.as_ref().map(|s| s.as_str()),
-        )?;
+        let name_name = option_write(self.conn, &mut self.name_cache, doc.name.as_deref())?;
+        let desc_name = option_write(self.conn, &mut self.desc_cache, doc.description.as_deref())?;
 
         let shared_cache = &mut self.packaging_cache;
         let pkg_name = option_write(self.conn, shared_cache, Some(&doc.object_info.packaging))?;
@@ -97,10 +154,28 @@ create table if not exists {name}_names (
         let classifier_name = option_write(
             self.conn,
             &mut self.classifier_cache,
-            doc.id.classifier.as_ref().map(|s| s.as_str()),
+            doc.id.classifier.as_deref(),
         )?;
 
-        self.conn
+        let params = [
+            &group_name as &dyn ToSql,
+            &artifact_name,
+            &doc.id.version.as_str(),
+            &classifier_name,
+            &ext_name,
+            &pkg_name,
+            &i64::try_from(doc.object_info.last_modified / 1000)?,
+            &doc.object_info.size.map(i64::try_from).inside_out()?,
+            &doc.checksum.map(hex::encode),
+            &attached_bool(doc.object_info.source_attached),
+            &attached_bool(doc.object_info.javadoc_attached),
+            &attached_bool(doc.object_info.signature_attached),
+            &name_name,
+            &desc_name,
+        ];
+
+        let inserted = self
+            .conn
             .prepare_cached(
                 r"
 insert into versions
@@ -124,25 +199,73 @@ insert into versions
    name_id,
    desc_id
   ) values (?,?,?,?,?,?,?,?,?,?,?,?,?,?)
+  on conflict do nothing
 ",
             )?
-            .insert([
-                &group_name as &dyn ToSql,
-                &artifact_name,
-                &doc.id.version.as_str(),
-                &classifier_name,
-                &ext_name,
-                &pkg_name,
-                &i64::try_from(doc.object_info.last_modified / 1000)?,
-                &doc.object_info.size.map(i64::try_from).inside_out()?,
-                &doc.checksum.map(hex::encode),
-                &attached_bool(doc.object_info.source_attached),
-                &attached_bool(doc.object_info.javadoc_attached),
-                &attached_bool(doc.object_info.signature_attached),
-                &name_name,
-                &desc_name,
+            .execute(params)?;
+        if inserted > 0 {
+            return Ok(Added::Inserted);
+        }
+
+        // later in the stream wins a tie, as with a chunk replacing an earlier one
+        let replaced = self
+            .conn
+            .prepare_cached(
+                r"
+update versions set
+   packaging_id=?6,
+
+   last_modified=?7,
+   size=?8,
+   checksum=?9,
+
+   source_attached=?10,
+   javadoc_attached=?11,
+   signature_attached=?12,
+
+   name_id=?13,
+   desc_id=?14
+where group_id=?1
+  and artifact_id=?2
+  and version=?3
+  and coalesce(classifier_id, 0)=coalesce(?4, 0)
+  and coalesce(extension_id, 0)=coalesce(?5, 0)
+  and last_modified<=?7
+",
+            )?
+            .execute(params)?;
+        Ok(if replaced > 0 {
+            Added::Replaced
+        } else {
+            Added::Older
+        })
+    }
+
+    /// Remove the artifact's rows, returning how many there were.
+    ///
+    /// Without an extension, as in old indexes, rows with any extension match.
+    pub fn delete(&mut self, id: &UniqId) -> Result<u64> {
+        let deleted = self
+            .conn
+            .prepare_cached(
+                r"
+delete from versions
+  where group_id=(select id from group_names where name=?1)
+    and artifact_id=(select id from artifact_names where name=?2)
+    and version=?3
+    and ((?4 is null and classifier_id is null)
+      or classifier_id=(select id from classifier_names where name=?4))
+    and (?5 is null
+      or extension_id=(select id from packaging_names where name=?5))",
+            )?
+            .execute([
+                Some(id.group.as_str()),
+                Some(id.artifact.as_str()),
+                Some(id.version.as_str()),
+                id.classifier.as_deref().filter(|name| empty_filter(name)),
+                id.extension.as_deref(),
             ])?;
-        Ok(())
+        Ok(u64::try_from(deleted)?)
     }
 }
 
@@ -192,6 +315,16 @@ fn string_write(conn: &rusqlite::Connection, cache: &mut Cache, val: &str) -> Re
     Ok(new_id)
 }
 
+fn load_cache(conn: &rusqlite::Connection, cache: &mut Cache) -> Result<()> {
+    let (table, cache) = cache;
+    let mut stmt = conn.prepare(&format!("select name, id from {table}_names"))?;
+    for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+        let (name, id) = row?;
+        cache.insert(name, id);
+    }
+    Ok(())
+}
+
 #[inline]
 fn write_examples(
     conn: &rusqlite::Connection,
@@ -1,61 +1,225 @@
+use std::fs;
 use std::io;
 use std::mem;
+use std::ops::AddAssign;
 use std::thread;
 
 use anyhow::anyhow;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 
+use super::meta::record;
+use super::meta::Provenance;
+use crate::compression;
 use crate::db;
+use crate::db::Added;
+use crate::incremental::Chunk;
 use crate::nexus::Doc;
 use crate::nexus::Event;
+use crate::nexus::IndexReader;
+use crate::nexus::ParallelReader;
+use crate::nexus::UniqId;
+
+/// What [`ingest`] or [`update`] did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IngestStats {
+    pub added: u64,
+    /// docs which replaced an existing row for the same artifact
+    pub replaced: u64,
+    /// docs ignored, as a row for the same artifact was modified later
+    pub older: u64,
+    /// rows removed by deletions
+    pub deleted: u64,
+    /// deletions for artifacts which weren't in the database
+    pub unmatched_deletes: u64,
+    /// records which couldn't be decoded, and were skipped
+    pub errors: u64,
+}
+
+impl AddAssign for IngestStats {
+    fn add_assign(&mut self, other: IngestStats) {
+        self.added += other.added;
+        self.replaced += other.replaced;
+        self.older += other.older;
+        self.deleted += other.deleted;
+        self.unmatched_deletes += other.unmatched_deletes;
+        self.errors += other.errors;
+    }
+}
+
+// as with `Event`, nearly everything is an `Add`
+#[allow(clippy::large_enum_variant)]
+enum Change {
+    Add(Doc),
+    Delete(UniqId),
+    /// everything was read; without this, nothing is committed
+    End(Provenance),
+}
 
 mod channel {
     use std::sync::mpsc;
-    pub type Sender = mpsc::SyncSender<super::Doc>;
-    pub type Receiver = mpsc::Receiver<super::Doc>;
+    pub type Sender = mpsc::SyncSender<super::Change>;
+    pub type Receiver = mpsc::Receiver<super::Change>;
+    pub type SendError = mpsc::SendError<super::Change>;
     pub fn new() -> (Sender, Receiver) {
         mpsc::sync_channel(65_536)
     }
 }
 
-pub fn ingest<R: io::BufRead>(from: R, conn: rusqlite::Connection) -> Result<rusqlite::Connection> {
+/// Add the docs in `from` to a new database, and apply its deletions, in order.
+///
+/// Records which can't be decoded are skipped, and counted; errors in the framing
+/// stop the ingest. What was read is recorded in the `meta` table; see [`read_meta`](super::read_meta).
+pub fn ingest<R: io::BufRead>(
+    from: R,
+    conn: rusqlite::Connection,
+) -> Result<(rusqlite::Connection, IngestStats)> {
+    run(from, conn, None)
+}
+
+/// Apply incremental chunks, e.g. from [`pending_chunks`](crate::incremental::pending_chunks),
+/// to a database built by [`ingest`].
+///
+/// Each chunk must follow [`last_chunk`](super::last_chunk), and be from the database's
+/// [`chain_id`](super::chain_id), if both know theirs. Each is applied in its own transaction,
+/// with `last_chunk` and `chain_id`, so an interrupted update can be resumed.
+pub fn update(
+    mut conn: rusqlite::Connection,
+    chunks: &[Chunk],
+) -> Result<(rusqlite::Connection, IngestStats)> {
+    let mut total = IngestStats::default();
+    for chunk in chunks {
+        let last = db::last_chunk(&conn)?
+            .ok_or_else(|| anyhow!("no last_chunk to update from; see set_last_chunk"))?;
+        ensure!(
+            Some(chunk.number) == last.checked_add(1),
+            "chunk {} doesn't follow the last applied, {last}",
+            chunk.number
+        );
+        if let (Some(ours), Some(theirs)) = (db::chain_id(&conn)?, &chunk.chain_id) {
+            ensure!(
+                ours == *theirs,
+                "chunk {} is from chain {theirs:?}, not {ours:?}; the database must be rebuilt",
+                chunk.number
+            );
+        }
+
+        let file = fs::File::open(&chunk.path)
+            .with_context(|| anyhow!("opening chunk {}: {:?}", chunk.number, chunk.path))?;
+        let from = compression::decompress(io::BufReader::new(file))?;
+        let (updated, stats) = run(from, conn, Some(chunk.clone()))
+            .with_context(|| anyhow!("applying chunk {}: {:?}", chunk.number, chunk.path))?;
+        conn = updated;
+        total += stats;
+    }
+    Ok((conn, total))
+}
+
+/// With a `chunk`, the database is updated, instead of being new.
+fn run<R: io::BufRead>(
+    from: R,
+    conn: rusqlite::Connection,
+    chunk: Option<Chunk>,
+) -> Result<(rusqlite::Connection, IngestStats)> {
     let (send, recv) = channel::new();
 
-    let writer = thread::spawn(move || write(conn, recv));
+    let writer = thread::spawn(move || write(conn, recv, chunk));
 
-    let local_error = crate::nexus::read(from, |event| {
-        match event {
-            Event::Doc(d) => send.send(d)?,
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
 
-            Event::Error { error, raw } => {
-                Err(error).with_context(|| anyhow!("processing {:?}", raw))?
-            }
-            Event::Delete(_) => (),
-        }
-        Ok(())
-    });
+    let local_error = read(from, threads, &send);
 
     mem::drop(send);
 
-    let conn = writer.join().map_err(|e| anyhow!("panic: {:?}", e))??;
+    let written = writer.join().map_err(|e| anyhow!("panic: {:?}", e))?;
+
+    match local_error {
+        // the writer has stopped, so has its own error, which is the interesting one
+        Err(e) if e.is::<channel::SendError>() => written,
+        // the writer only fails, with "reading stopped early", if the reader did
+        Err(e) => Err(e),
+        Ok(()) => written,
+    }
+}
 
-    local_error?;
+fn read<R: io::BufRead>(from: R, threads: usize, send: &channel::Sender) -> Result<()> {
+    let reader = IndexReader::new(from)?;
+    let mut provenance = Provenance {
+        timestamp_ms: reader.timestamp_ms(),
+        ..Provenance::default()
+    };
+
+    for event in ParallelReader::new(reader, threads) {
+        match event? {
+            Event::Doc(d) => {
+                provenance.docs += 1;
+                send.send(Change::Add(d))?
+            }
+            Event::Delete(id) => {
+                provenance.deletes += 1;
+                send.send(Change::Delete(id))?
+            }
+            Event::Error { .. } => provenance.errors += 1,
+            Event::Descriptor { repository_id, .. } => {
+                provenance.repository_id = Some(repository_id)
+            }
+            Event::Groups { .. } => (),
+        }
+    }
 
-    Ok(conn)
+    send.send(Change::End(provenance))?;
+    Ok(())
 }
 
-fn write(mut conn: rusqlite::Connection, recv: channel::Receiver) -> Result<rusqlite::Connection> {
+fn write(
+    mut conn: rusqlite::Connection,
+    recv: channel::Receiver,
+    chunk: Option<Chunk>,
+) -> Result<(rusqlite::Connection, IngestStats)> {
     let tran = conn.transaction()?;
+    let mut stats = IngestStats::default();
+    let mut provenance = None;
 
     {
-        let mut db = db::DbBuilder::new(&tran)?;
-        while let Ok(doc) = recv.recv() {
-            db.add(&doc).with_context(|| anyhow!("adding {doc:?}"))?;
+        let mut db = match chunk {
+            Some(_) => db::DbBuilder::open(&tran)?,
+            None => db::DbBuilder::new(&tran)?,
+        };
+        while let Ok(change) = recv.recv() {
+            match change {
+                Change::Add(doc) => {
+                    match db.add(&doc).with_context(|| anyhow!("adding {doc:?}"))? {
+                        Added::Inserted => stats.added += 1,
+                        Added::Replaced => stats.replaced += 1,
+                        Added::Older => stats.older += 1,
+                    }
+                }
+                Change::Delete(id) => {
+                    match db.delete(&id).with_context(|| anyhow!("deleting {id}"))? {
+                        0 => stats.unmatched_deletes += 1,
+                        rows => stats.deleted += rows,
+                    }
+                }
+                Change::End(read) => {
+                    stats.errors = read.errors;
+                    provenance = Some(read);
+                    break;
+                }
+            }
+        }
+    }
+
+    let provenance = provenance.ok_or_else(|| anyhow!("reading stopped early"))?;
+    record(&tran, &provenance)?;
+    if let Some(chunk) = chunk {
+        db::set_last_chunk(&tran, chunk.number)?;
+        if let Some(chain_id) = &chunk.chain_id {
+            db::set_chain_id(&tran, chain_id)?;
         }
     }
 
     tran.commit()?;
 
-    Ok(conn)
+    Ok((conn, stats))
 }
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+
+use crate::incremental::IndexProperties;
+
+const LAST_CHUNK: &str = "last_chunk";
+const CHAIN_ID: &str = "chain_id";
+
+/// What an ingest read, as opposed to what it did to the database.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Provenance {
+    /// from the index's header
+    pub timestamp_ms: i64,
+    /// from the descriptor record, if there was one
+    pub repository_id: Option<String>,
+    pub docs: u64,
+    pub deletes: u64,
+    pub errors: u64,
+}
+
+/// Facts about the database as a whole, e.g. which incremental chunk it's up to.
+pub(super) const TABLE: &str = r"
+create table if not exists meta (
+  key varchar primary key,
+  value varchar not null
+)";
+
+/// For databases which haven't been [`migrate`](super::migrate)d.
+pub(super) fn create_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(TABLE)?;
+    Ok(())
+}
+
+/// Everything in the `meta` table, which is empty if there isn't one yet:
+///  * `nexers_version`: of the last ingest or update,
+///  * `ingested_at`: when that finished, in milliseconds since the epoch,
+///  * `index_timestamp`: from the header of the last index or chunk read, likewise,
+///  * `repository_id`: e.g. `central`,
+///  * `chain_id`: of the incremental chunks; see [`chain_id`],
+///  * `last_chunk`; see [`last_chunk`],
+///  * `docs`, `deletes` and `errors`: the records read by the last ingest or update,
+///  * `total_docs`, `total_deletes` and `total_errors`: likewise, over all of them.
+pub fn read_meta(conn: &Connection) -> Result<BTreeMap<String, String>> {
+    if !has_table(conn)? {
+        return Ok(BTreeMap::new());
+    }
+    Ok(conn
+        .prepare("select key, value from meta")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?)
+}
+
+/// The number of the last incremental chunk applied, if any; see
+/// [`incremental`](crate::incremental).
+pub fn last_chunk(conn: &Connection) -> Result<Option<u32>> {
+    get(conn, LAST_CHUNK)?
+        .map(|value| {
+            value
+                .parse()
+                .with_context(|| anyhow!("reading {LAST_CHUNK}: {value:?}"))
+        })
+        .transpose()
+}
+
+/// Record that the database is up to date with chunk `number`, e.g. after [`ingest`](super::ingest)ing
+/// a full index, with the `last_incremental` from its [`IndexProperties`]; see also [`set_properties`].
+pub fn set_last_chunk(conn: &Connection, number: u32) -> Result<()> {
+    create_table(conn)?;
+    set(conn, LAST_CHUNK, &number.to_string())
+}
+
+/// The `nexus.index.chain-id` the incremental chunks must come from, if known.
+pub fn chain_id(conn: &Connection) -> Result<Option<String>> {
+    get(conn, CHAIN_ID)
+}
+
+/// Record the chain the database follows, as [`update`](super::update) does.
+pub fn set_chain_id(conn: &Connection, chain_id: &str) -> Result<()> {
+    create_table(conn)?;
+    set(conn, CHAIN_ID, chain_id)
+}
+
+/// Record where an index came from, after [`ingest`](super::ingest)ing it:
+/// the chain and repository ids, and the last chunk, if present.
+pub fn set_properties(conn: &Connection, props: &IndexProperties) -> Result<()> {
+    create_table(conn)?;
+    if let Some(chain_id) = &props.chain_id {
+        set(conn, CHAIN_ID, chain_id)?;
+    }
+    if let Some(repository_id) = &props.repository_id {
+        set(conn, "repository_id", repository_id)?;
+    }
+    if let Some(number) = props.last_incremental {
+        set(conn, LAST_CHUNK, &number.to_string())?;
+    }
+    Ok(())
+}
+
+pub(super) fn record(conn: &Connection, read: &Provenance) -> Result<()> {
+    create_table(conn)?;
+    set(conn, "nexers_version", env!("CARGO_PKG_VERSION"))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    set(conn, "ingested_at", &now.to_string())?;
+    set(conn, "index_timestamp", &read.timestamp_ms.to_string())?;
+    if let Some(repository_id) = &read.repository_id {
+        set(conn, "repository_id", repository_id)?;
+    }
+    for (key, count) in [
+        ("docs", read.docs),
+        ("deletes", read.deletes),
+        ("errors", read.errors),
+    ] {
+        set(conn, key, &count.to_string())?;
+        let total = format!("total_{key}");
+        let previous = match get(conn, &total)? {
+            Some(value) => value
+                .parse::<u64>()
+                .with_context(|| anyhow!("reading {total}: {value:?}"))?,
+            None => 0,
+        };
+        set(conn, &total, &(previous + count).to_string())?;
+    }
+    Ok(())
+}
+
+/// Readers don't create the table, so work on read-only connections to older databases.
+fn has_table(conn: &Connection) -> Result<bool> {
+    Ok(conn
+        .prepare_cached("select 1 from sqlite_master where type='table' and name='meta'")?
+        .exists([])?)
+}
+
+/// `None` if the table isn't there.
+fn get(conn: &Connection, key: &str) -> Result<Option<String>> {
+    if !has_table(conn)? {
+        return Ok(None);
+    }
+    Ok(conn
+        .query_row("select value from meta where key=?", [key], |row| {
+            row.get(0)
+        })
+        .optional()?)
+}
+
+fn set(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "insert or replace into meta (key, value) values (?, ?)",
+        [key, value],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anyhow::anyhow;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+
+/// Each step, in order; a database at version `n` has had the first `n` applied,
+/// which is recorded in `pragma user_version`. Only ever append to this.
+/// Indexes only for queries are deliberately missing, as they slow down an ingest;
+/// see [`index_checksums`](super::index_checksums).
+const MIGRATIONS: &[&[&str]] = &[
+    // 1: `SCHEMA`, which is all there was before versioning
+    &[super::SCHEMA],
+    // 2: one row per artifact, for `DbBuilder::add` to upsert on;
+    // older databases can have duplicates, of which the newest is kept
+    &[super::builder::DEDUPLICATE, super::builder::UNIQUE_INDEX],
+    // 3: see `read_meta`
+    &[super::meta::TABLE],
+    // 4: the view from `workbook.sql`, which may have been run by hand
+    &[r"
+create view if not exists vers as
+select id,
+       (select name from group_names where id = group_id)           as `group`,
+       (select name from artifact_names where id = artifact_id)     as artifact,
+       version,
+       (select name from classifier_names where id = classifier_id) as classifier,
+       (select name from packaging_names where id = packaging_id)   as packaging,
+       (select name from packaging_names where id = extension_id)   as extension,
+       datetime(last_modified, 'unixepoch')                         as last_modified,
+       size,
+       checksum,
+       source_attached,
+       javadoc_attached,
+       signature_attached,
+       name_id,
+       desc_id
+from versions;
+"],
+];
+
+/// The version [`migrate`] brings databases up to.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// The database's schema version: `0` if it's empty, and `1` if it was made
+/// with [`SCHEMA`](super::SCHEMA) before there were versions.
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if 0 != version {
+        return Ok(version);
+    }
+    let has_versions = conn
+        .query_row(
+            "select 1 from sqlite_master where type='table' and name='versions'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    Ok(if has_versions { 1 } else { 0 })
+}
+
+/// Bring the database up to [`SCHEMA_VERSION`], from empty, or from any
+/// earlier version, returning the version it was at.
+///
+/// Each step is applied atomically, even outside a transaction. Databases from
+/// a newer nexers are refused, rather than guessed at.
+pub fn migrate(conn: &Connection) -> Result<u32> {
+    let from = schema_version(conn)?;
+    ensure!(
+        from <= SCHEMA_VERSION,
+        "database is at schema version {from}, but this nexers only knows up to {SCHEMA_VERSION}"
+    );
+
+    for (version, steps) in (1..).zip(MIGRATIONS).skip(usize::try_from(from)?) {
+        conn.execute_batch("savepoint migrate")?;
+        let applied = steps
+            .iter()
+            .try_for_each(|sql| conn.execute_batch(sql))
+            .and_then(|()| conn.pragma_update(None, "user_version", version));
+        match applied {
+            Ok(()) => conn.execute_batch("release migrate")?,
+            Err(e) => {
+                conn.execute_batch("rollback to migrate; release migrate")?;
+                return Err(e).with_context(|| anyhow!("migrating to schema version {version}"));
+            }
+        }
+    }
+
+    Ok(from)
+}
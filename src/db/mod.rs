@@ -1,22 +1,124 @@
 use anyhow::Result;
 use rusqlite::Connection;
 
+use crate::nexus::Checksum;
+use crate::nexus::UniqId;
+use crate::version::MavenVersion;
+
 mod builder;
 mod ingest;
+mod meta;
+mod migrate;
 
+pub use self::builder::Added;
 pub use self::builder::DbBuilder;
 pub use self::ingest::ingest;
+pub use self::ingest::update;
+pub use self::ingest::IngestStats;
+pub use self::meta::chain_id;
+pub use self::meta::last_chunk;
+pub use self::meta::read_meta;
+pub use self::meta::set_chain_id;
+pub use self::meta::set_last_chunk;
+pub use self::meta::set_properties;
+pub use self::migrate::migrate;
+pub use self::migrate::schema_version;
+pub use self::migrate::SCHEMA_VERSION;
 
+/// The original schema, with no version; prefer [`migrate`], which starts from this.
 pub const SCHEMA: &str = include_str!("../../schema.sql");
 
+/// All the known versions of an artifact, oldest first, as Maven orders them.
 pub fn find_versions(conn: &Connection, group: &str, artifact: &str) -> Result<Vec<String>> {
-    Ok(conn
+    let mut versions = conn
         .prepare_cached(
             r"
 select version from versions
   where group_id=(select id from group_names where name=?)
     and artifact_id=(select id from artifact_names where name=?)",
         )?
-        .query_map([group, artifact], |row| row.get(0))?
-        .collect::<Result<Vec<String>, _>>()?)
+        .query_map([group, artifact], |row| {
+            Ok(MavenVersion::parse(&row.get::<_, String>(0)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    versions.sort();
+    Ok(versions.into_iter().map(|v| v.to_string()).collect())
+}
+
+/// Speeds up [`find_by_checksum`]; best done after ingesting.
+///
+/// Like [`index_last_modified`], this isn't created by [`migrate`], so isn't there during an
+/// [`ingest`]: maintaining it row by row is far slower than building it at the end, especially
+/// as checksums are random. Once created, [`update`] keeps it up to date.
+pub fn index_checksums(conn: &Connection) -> Result<()> {
+    conn.execute_batch("create index if not exists versions_checksum on versions (checksum)")?;
+    Ok(())
+}
+
+/// Speeds up queries by upload time, e.g. for what's new; best done after ingesting,
+/// as with [`index_checksums`].
+pub fn index_last_modified(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "create index if not exists versions_last_modified on versions (last_modified)",
+    )?;
+    Ok(())
+}
+
+/// The artifacts with this SHA-1; see [`index_checksums`].
+///
+/// The extension is the one from the `i` field.
+pub fn find_by_checksum(conn: &Connection, checksum: &Checksum) -> Result<Vec<UniqId>> {
+    Ok(conn
+        .prepare_cached(
+            r"
+select
+  (select name from group_names where id=group_id),
+  (select name from artifact_names where id=artifact_id),
+  version,
+  (select name from classifier_names where id=classifier_id),
+  (select name from packaging_names where id=extension_id)
+from versions where checksum=?",
+        )?
+        .query_map([hex::encode(checksum)], |row| {
+            Ok(UniqId {
+                group: row.get::<_, String>(0)?.into(),
+                artifact: row.get::<_, String>(1)?.into(),
+                version: row.get::<_, String>(2)?.into(),
+                classifier: row.get::<_, Option<String>>(3)?.map(Into::into),
+                extension: row.get::<_, Option<String>>(4)?.map(Into::into),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Adds SQL functions to `conn`:
+///  * `purl(group, artifact, version[, classifier[, extension]])`, as [`crate::purl::to_purl`];
+///    `null`s are absent.
+///
+/// e.g. `select purl(g.name, a.name, version) from versions
+///   join group_names g on g.id=group_id join artifact_names a on a.id=artifact_id`
+#[cfg(feature = "sql-functions")]
+pub fn add_functions(conn: &Connection) -> Result<()> {
+    use rusqlite::functions::FunctionFlags;
+
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    for args in 3..=5 {
+        conn.create_scalar_function("purl", args, flags, |ctx| {
+            let optional = |i: usize| -> rusqlite::Result<Option<String>> {
+                if i < ctx.len() {
+                    ctx.get(i)
+                } else {
+                    Ok(None)
+                }
+            };
+            Ok(crate::purl::to_purl(&UniqId {
+                group: ctx.get::<String>(0)?.into(),
+                artifact: ctx.get::<String>(1)?.into(),
+                version: ctx.get::<String>(2)?.into(),
+                classifier: optional(3)?.map(Into::into),
+                extension: optional(4)?.map(Into::into),
+            }))
+        })?;
+    }
+    Ok(())
 }
@@ -0,0 +1,244 @@
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+/// Where in the index an [`Error`] happened.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Location {
+    /// The record's position in the stream, counting from zero; `None` for the header.
+    pub record: Option<u64>,
+    /// The field's position in the record, if the problem is with a single field.
+    pub field: Option<usize>,
+    /// Bytes from the start of the stream to the start of the value being read.
+    pub offset: u64,
+}
+
+/// Everything which can go wrong while reading an index.
+///
+/// Errors in the framing (e.g. [`Error::Truncated`]) end the stream, as there's no way to
+/// know where the next record starts, unless [`ReaderOptions::resync`](crate::nexus::ReaderOptions::resync)
+/// is set. The rest are about a single record, and are reported in
+/// [`Event::Error`](crate::nexus::Event::Error).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The underlying reader failed.
+    Io {
+        location: Location,
+        source: io::Error,
+    },
+    /// The stream ended part way through the header or a record.
+    Truncated { location: Location },
+    /// The version byte was not `1`.
+    BadHeader { location: Location, version: i8 },
+    /// A field had flags this format doesn't define.
+    BadFieldFlags { location: Location, flags: u8 },
+    /// A count or length which can't be negative (or zero), was.
+    BadLength {
+        location: Location,
+        what: &'static str,
+        len: i64,
+    },
+    /// A count or length was larger than allowed by the [`ReaderOptions`](crate::nexus::ReaderOptions).
+    LimitExceeded {
+        location: Location,
+        what: &'static str,
+        len: usize,
+        limit: usize,
+    },
+    /// A name or value was neither utf-8, nor Java's "modified" utf-8.
+    BadUtf8 { location: Location, lossy: String },
+    /// A record which isn't a doc, a deletion, or any of the other known types.
+    UnrecognisedRecord { location: Location },
+    /// A doc without one of `u`, `i` or `m`.
+    MissingField {
+        location: Location,
+        name: &'static str,
+    },
+    /// The `u` (or `del`) field didn't have the `group|artifact|version|classifier` form.
+    MalformedUniq {
+        location: Location,
+        value: String,
+        reason: &'static str,
+    },
+    /// The `i` field didn't have the `packaging|time|size|0|0|0|extension` form.
+    MalformedInfo {
+        location: Location,
+        value: String,
+        reason: &'static str,
+    },
+    /// The `m` field wasn't a number.
+    MalformedModified { location: Location, value: String },
+    /// The `IDXINFO` field didn't have the `version|repository` form.
+    MalformedDescriptor { location: Location, value: String },
+    /// With [`ReaderOptions::resync`](crate::nexus::ReaderOptions::resync), a framing error
+    /// was recovered from by skipping ahead to the next thing which looked like a record.
+    Skipped {
+        location: Location,
+        /// the byte offsets which were thrown away
+        skipped: Range<u64>,
+        cause: Box<Error>,
+    },
+}
+
+impl Error {
+    pub fn location(&self) -> &Location {
+        match self {
+            Error::Io { location, .. }
+            | Error::Truncated { location }
+            | Error::BadHeader { location, .. }
+            | Error::BadFieldFlags { location, .. }
+            | Error::BadLength { location, .. }
+            | Error::LimitExceeded { location, .. }
+            | Error::BadUtf8 { location, .. }
+            | Error::UnrecognisedRecord { location }
+            | Error::MissingField { location, .. }
+            | Error::MalformedUniq { location, .. }
+            | Error::MalformedInfo { location, .. }
+            | Error::MalformedModified { location, .. }
+            | Error::MalformedDescriptor { location, .. }
+            | Error::Skipped { location, .. } => location,
+        }
+    }
+
+    fn location_mut(&mut self) -> &mut Location {
+        match self {
+            Error::Io { location, .. }
+            | Error::Truncated { location }
+            | Error::BadHeader { location, .. }
+            | Error::BadFieldFlags { location, .. }
+            | Error::BadLength { location, .. }
+            | Error::LimitExceeded { location, .. }
+            | Error::BadUtf8 { location, .. }
+            | Error::UnrecognisedRecord { location }
+            | Error::MissingField { location, .. }
+            | Error::MalformedUniq { location, .. }
+            | Error::MalformedInfo { location, .. }
+            | Error::MalformedModified { location, .. }
+            | Error::MalformedDescriptor { location, .. }
+            | Error::Skipped { location, .. } => location,
+        }
+    }
+
+    /// `io::Error`s are mostly the stream ending early.
+    pub(crate) fn from_io(source: io::Error, offset: u64) -> Error {
+        let location = Location {
+            offset,
+            ..Location::default()
+        };
+        if io::ErrorKind::UnexpectedEof == source.kind() {
+            Error::Truncated { location }
+        } else {
+            Error::Io { location, source }
+        }
+    }
+
+    /// Low-level reads only know the offset; the reader fills in the rest.
+    pub(crate) fn in_record(mut self, record: u64) -> Error {
+        self.location_mut().record = Some(record);
+        self
+    }
+
+    /// Whether this is a problem with the framing, which a resync could recover from.
+    pub(crate) fn is_framing(&self) -> bool {
+        matches!(
+            self,
+            Error::Truncated { .. }
+                | Error::BadFieldFlags { .. }
+                | Error::BadLength { .. }
+                | Error::LimitExceeded { .. }
+                | Error::BadUtf8 { .. }
+        )
+    }
+
+    /// The input has run dry for now, and the read can be retried when there's more.
+    pub(crate) fn is_would_block(&self) -> bool {
+        matches!(self, Error::Io { source, .. } if io::ErrorKind::WouldBlock == source.kind())
+    }
+
+    pub(crate) fn in_field(mut self, field: usize) -> Error {
+        self.location_mut().field = Some(field);
+        self
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.record {
+            Some(record) => write!(f, "record {record}")?,
+            None => write!(f, "header")?,
+        }
+        if let Some(field) = self.field {
+            write!(f, ", field {field}")?;
+        }
+        write!(f, " (byte {})", self.offset)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { location, .. } => write!(f, "reading {location}"),
+            Error::Truncated { location } => write!(f, "truncated input in {location}"),
+            Error::BadHeader { location, version } => {
+                write!(f, "unsupported version byte {version} in {location}")
+            }
+            Error::BadFieldFlags { location, flags } => {
+                write!(f, "invalid field flags {flags:#04x} in {location}")
+            }
+            Error::BadLength {
+                location,
+                what,
+                len,
+            } => write!(f, "invalid {what} {len} in {location}"),
+            Error::LimitExceeded {
+                location,
+                what,
+                len,
+                limit,
+            } => write!(f, "{what} too large: {len} > {limit} in {location}"),
+            Error::BadUtf8 { location, lossy } => {
+                write!(f, "invalid 'modified' utf-8 in {location}: {lossy:?}")
+            }
+            Error::UnrecognisedRecord { location } => {
+                write!(f, "unrecognised doc type in {location}")
+            }
+            Error::MissingField { location, name } => {
+                write!(f, "no {name:?} field in {location}")
+            }
+            Error::MalformedUniq {
+                location,
+                value,
+                reason,
+            } => write!(f, "reading 'u': {reason}: {value:?} in {location}"),
+            Error::MalformedInfo {
+                location,
+                value,
+                reason,
+            } => write!(f, "reading 'i': {reason}: {value:?} in {location}"),
+            Error::MalformedModified { location, value } => {
+                write!(f, "reading 'm': {value:?} in {location}")
+            }
+            Error::MalformedDescriptor { location, value } => {
+                write!(f, "malformed IDXINFO: {value:?} in {location}")
+            }
+            Error::Skipped {
+                location, skipped, ..
+            } => write!(
+                f,
+                "skipped bytes {}..{} after a framing error in {location}",
+                skipped.start, skipped.end
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Skipped { cause, .. } => Some(cause.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Find out what files are, from their SHA-1s, e.g. for jars vendored without any metadata.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::nexus::Checksum;
+use crate::nexus::Doc;
+use crate::nexus::Event;
+use crate::nexus::UniqId;
+
+/// A file, and the artifacts it could be.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identified {
+    pub path: PathBuf,
+    pub checksum: Checksum,
+    /// empty if nothing matched
+    pub matches: Vec<UniqId>,
+}
+
+/// Artifacts by their SHA-1, in memory; `db::find_by_checksum` avoids holding
+/// the whole index.
+#[derive(Clone, Debug, Default)]
+pub struct ChecksumMap {
+    inner: HashMap<Checksum, Vec<UniqId>>,
+}
+
+impl ChecksumMap {
+    pub fn new() -> ChecksumMap {
+        ChecksumMap::default()
+    }
+
+    /// Every doc with a checksum in the index.
+    pub fn from_index<R: BufRead>(from: R) -> Result<ChecksumMap> {
+        let mut map = ChecksumMap::new();
+        crate::nexus::read(from, |event| {
+            if let Event::Doc(doc) = event {
+                map.add(&doc);
+            }
+            Ok(())
+        })?;
+        Ok(map)
+    }
+
+    /// Docs without a checksum are ignored.
+    pub fn add(&mut self, doc: &Doc) {
+        if let Some(checksum) = doc.checksum {
+            self.inner.entry(checksum).or_default().push(doc.id.clone());
+        }
+    }
+
+    pub fn get(&self, checksum: &Checksum) -> &[UniqId] {
+        self.inner.get(checksum).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// Hash the file at `path`, or every file under it, in order, and look them up.
+///
+/// Symlinks aren't followed. `lookup` is e.g. `|c| Ok(map.get(c).to_vec())`
+/// for a [`ChecksumMap`], or `|c| db::find_by_checksum(&conn, c)`.
+pub fn identify<F>(path: impl AsRef<Path>, mut lookup: F) -> Result<Vec<Identified>>
+where
+    F: FnMut(&Checksum) -> Result<Vec<UniqId>>,
+{
+    let mut ret = Vec::new();
+    for path in files(path.as_ref())? {
+        let checksum = sha1_file(&path)?;
+        let matches = lookup(&checksum).with_context(|| anyhow!("looking up {path:?}"))?;
+        ret.push(Identified {
+            path,
+            checksum,
+            matches,
+        });
+    }
+    Ok(ret)
+}
+
+pub fn sha1<R: Read>(mut from: R) -> io::Result<Checksum> {
+    let mut hasher = sha1_smol::Sha1::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) => return Ok(hasher.digest().bytes()),
+            Ok(read) => hasher.update(&buf[..read]),
+            Err(e) if io::ErrorKind::Interrupted == e.kind() => (),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+pub fn sha1_file(path: impl AsRef<Path>) -> Result<Checksum> {
+    let path = path.as_ref();
+    let file = fs::File::open(path).with_context(|| anyhow!("opening {path:?}"))?;
+    sha1(file).with_context(|| anyhow!("reading {path:?}"))
+}
+
+/// `path`, if it's a file, or all the files under it, sorted.
+fn files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !fs::symlink_metadata(path)
+        .with_context(|| anyhow!("inspecting {path:?}"))?
+        .is_dir()
+    {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut ret = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).with_context(|| anyhow!("listing {dir:?}"))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                ret.push(entry.path());
+            }
+        }
+    }
+    ret.sort();
+    Ok(ret)
+}
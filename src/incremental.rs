@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::time::days_from_civil;
+
+pub const PROPERTIES_FILE: &str = "nexus-maven-repository-index.properties";
+
+/// The `.properties` file published next to an index, describing the incremental chunks.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IndexProperties {
+    /// `nexus.index.id`, e.g. `central`
+    pub repository_id: Option<String>,
+    /// `nexus.index.chain-id`; chunk numbers are only meaningful within a chain
+    pub chain_id: Option<String>,
+    /// `nexus.index.timestamp`, in milliseconds since the epoch
+    pub timestamp_ms: Option<i64>,
+    /// `nexus.index.last-incremental`, the newest chunk
+    pub last_incremental: Option<u32>,
+    /// the chunk numbers listed as `nexus.index.incremental-*`, ascending
+    pub incrementals: Vec<u32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Chunk {
+    pub number: u32,
+    pub path: PathBuf,
+    /// from the `.properties` which listed it, if that had one
+    pub chain_id: Option<String>,
+}
+
+impl IndexProperties {
+    pub fn read<R: BufRead>(mut from: R) -> Result<IndexProperties> {
+        let mut buf = Vec::new();
+        from.read_to_end(&mut buf)?;
+        // java writes these files as latin-1, but all the values we care about are ascii
+        let props = parse_properties(&String::from_utf8_lossy(&buf));
+
+        let mut incrementals = Vec::new();
+        for (key, value) in &props {
+            if key.starts_with("nexus.index.incremental-") {
+                incrementals.push(
+                    value
+                        .parse::<u32>()
+                        .with_context(|| anyhow!("reading {key}: {value:?}"))?,
+                );
+            }
+        }
+        incrementals.sort_unstable();
+        incrementals.dedup();
+
+        Ok(IndexProperties {
+            repository_id: props.get("nexus.index.id").cloned(),
+            chain_id: props.get("nexus.index.chain-id").cloned(),
+            timestamp_ms: props
+                .get("nexus.index.timestamp")
+                .map(|v| read_timestamp(v).with_context(|| anyhow!("reading timestamp: {v:?}")))
+                .transpose()?,
+            last_incremental: props
+                .get("nexus.index.last-incremental")
+                .map(|v| {
+                    v.parse::<u32>()
+                        .with_context(|| anyhow!("reading last-incremental: {v:?}"))
+                })
+                .transpose()?,
+            incrementals,
+        })
+    }
+
+    pub fn open(dir: impl AsRef<Path>) -> Result<IndexProperties> {
+        let path = dir.as_ref().join(PROPERTIES_FILE);
+        let file = fs::File::open(&path).with_context(|| anyhow!("opening {path:?}"))?;
+        IndexProperties::read(io::BufReader::new(file))
+    }
+
+    /// The chunk numbers, in application order, needed to bring an index which has
+    /// had `last_applied` applied up to date. Fails if any of them are no longer published,
+    /// in which case the full index must be downloaded again.
+    pub fn pending(&self, last_applied: u32) -> Result<Vec<u32>> {
+        let last = self
+            .last_incremental
+            .ok_or_else(|| anyhow!("no last-incremental; index has no chunks"))?;
+
+        ensure!(
+            last_applied <= last,
+            "last applied chunk {last_applied} is newer than the published {last}; different chain?"
+        );
+
+        // already up to date, if nothing can follow `last_applied`
+        let Some(next) = last_applied.checked_add(1) else {
+            return Ok(Vec::new());
+        };
+        let needed = (next..=last).collect::<Vec<_>>();
+        for number in &needed {
+            if self.incrementals.binary_search(number).is_err() {
+                bail!(
+                    "chunk {number} is no longer published; chain is broken after {last_applied}"
+                );
+            }
+        }
+
+        Ok(needed)
+    }
+}
+
+pub fn chunk_file_name(number: u32) -> String {
+    format!("nexus-maven-repository-index.{number}.gz")
+}
+
+/// Read the properties from `dir`, and find the downloaded chunks which are still to be applied.
+pub fn pending_chunks(dir: impl AsRef<Path>, last_applied: u32) -> Result<Vec<Chunk>> {
+    let dir = dir.as_ref();
+    let props = IndexProperties::open(dir)?;
+
+    props
+        .pending(last_applied)?
+        .into_iter()
+        .map(|number| {
+            let path = dir.join(chunk_file_name(number));
+            ensure!(path.is_file(), "chunk {number} not downloaded: {path:?}");
+            Ok(Chunk {
+                number,
+                path,
+                chain_id: props.chain_id.clone(),
+            })
+        })
+        .collect()
+}
+
+/// `yyyyMMddHHmmss.SSS Z`, e.g. `20240227012509.123 +0000`
+fn read_timestamp(value: &str) -> Result<i64> {
+    let (stamp, zone) = value.split_once(' ').ok_or_else(|| anyhow!("no zone"))?;
+    let (stamp, millis) = stamp.split_once('.').unwrap_or((stamp, "0"));
+    ensure!(
+        (1..=3).contains(&millis.len()) && millis.bytes().all(|b| b.is_ascii_digit()),
+        "bad millis"
+    );
+
+    ensure!(
+        stamp.len() == 14 && stamp.bytes().all(|b| b.is_ascii_digit()),
+        "bad date"
+    );
+    let num =
+        |range: std::ops::Range<usize>, valid: std::ops::RangeInclusive<u32>| -> Result<u32> {
+            let value = stamp[range].parse()?;
+            ensure!(valid.contains(&value), "out of range: {value}");
+            Ok(value)
+        };
+
+    let days = days_from_civil(
+        i64::from(num(0..4, 0..=9999)?),
+        num(4..6, 1..=12)?,
+        num(6..8, 1..=31)?,
+    );
+    let seconds = days * 86_400
+        + i64::from(num(8..10, 0..=23)?) * 3_600
+        + i64::from(num(10..12, 0..=59)?) * 60
+        + i64::from(num(12..14, 0..=59)?);
+
+    ensure!(
+        zone.len() == 5
+            && (zone.starts_with('+') || zone.starts_with('-'))
+            && zone.bytes().skip(1).all(|b| b.is_ascii_digit()),
+        "bad zone"
+    );
+    let (hours, minutes) = (zone[1..3].parse::<u32>()?, zone[3..].parse::<u32>()?);
+    ensure!(hours <= 23 && minutes <= 59, "zone out of range");
+    let offset = i64::from(hours) * 3_600 + i64::from(minutes) * 60;
+    let offset = if zone.starts_with('-') {
+        -offset
+    } else {
+        offset
+    };
+
+    (seconds - offset)
+        .checked_mul(1_000)
+        .and_then(|ms| ms.checked_add(millis.parse().ok()?))
+        .ok_or_else(|| anyhow!("timestamp out of range"))
+}
+
+/// Just enough of `java.util.Properties` for the files `maven-indexer` writes.
+fn parse_properties(contents: &str) -> HashMap<String, String> {
+    let mut ret = HashMap::new();
+    let mut lines = contents.lines();
+
+    while let Some(line) = lines.next() {
+        let mut line = line.trim_start().to_string();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+
+        while ends_with_continuation(&line) {
+            line.pop();
+            match lines.next() {
+                Some(next) => line.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+
+        let mut key = String::new();
+        let mut chars = line.chars();
+        let mut escaped = false;
+        for c in chars.by_ref() {
+            if escaped {
+                key.push(unescape(c));
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '=' || c == ':' || c.is_whitespace() {
+                break;
+            } else {
+                key.push(c);
+            }
+        }
+
+        let rest = chars.as_str().trim_start();
+        let rest = rest
+            .strip_prefix(['=', ':'])
+            .map(|r| r.trim_start())
+            .unwrap_or(rest);
+
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('u') => {
+                    let hex = chars.by_ref().take(4).collect::<String>();
+                    if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        value.push(c);
+                    }
+                }
+                Some(c) => value.push(unescape(c)),
+                None => (),
+            }
+        }
+
+        ret.insert(key, value);
+    }
+
+    ret
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+fn unescape(c: char) -> char {
+    match c {
+        't' => '\t',
+        'n' => '\n',
+        'r' => '\r',
+        'f' => '\x0c',
+        other => other,
+    }
+}
@@ -1,61 +1,436 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::io;
 use std::io::BufRead;
+use std::io::Write;
 
 use anyhow::anyhow;
+use anyhow::Context;
 use anyhow::Result;
-use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
 use byteorder::BE;
+#[cfg(feature = "async")]
+use tokio::io::AsyncBufRead;
+#[cfg(feature = "async")]
+use tokio::io::AsyncBufReadExt;
 
+use crate::Error;
+use crate::Location;
+
+/// Reads Java's `DataInput` encodings, keeping track of the offset for errors.
 pub struct DataInput<R: BufRead> {
     inner: R,
+    position: u64,
+    /// bytes handed back by `unread`, to be read before `inner`
+    pending: VecDeque<u8>,
+    /// bytes read since `start_capture`
+    capture: Option<Vec<u8>>,
 }
 
 impl<R: BufRead> DataInput<R> {
     pub fn new(inner: R) -> Self {
-        DataInput { inner }
+        DataInput {
+            inner,
+            position: 0,
+            pending: VecDeque::new(),
+            capture: None,
+        }
+    }
+
+    /// Bytes consumed so far.
+    pub fn position(&self) -> u64 {
+        self.position
     }
 
-    pub fn read_byte(&mut self) -> Result<i8, io::Error> {
-        self.inner.read_i8()
+    pub fn read_byte(&mut self) -> Result<i8, Error> {
+        Ok(i8::from_be_bytes(self.read_array()?))
     }
 
     // `char`? Sigh.
-    pub fn read_unsigned_short(&mut self) -> Result<u16, io::Error> {
-        self.inner.read_u16::<BE>()
+    pub fn read_unsigned_short(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_int(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_long(&mut self) -> Result<i64, Error> {
+        Ok(i64::from_be_bytes(self.read_array()?))
     }
 
-    pub fn read_int(&mut self) -> Result<i32, io::Error> {
-        self.inner.read_i32::<BE>()
+    pub fn read_utf8(&mut self, len: usize) -> Result<String, Error> {
+        let start = self.position;
+        let mut buf = Vec::new();
+        // grow as the data actually arrives, instead of trusting `len`
+        while buf.len() < len {
+            let filled = buf.len();
+            buf.resize(filled + (len - filled).min(GROWTH), 0);
+            self.fill(&mut buf[filled..])
+                .map_err(|e| Error::from_io(e, start))?;
+        }
+
+        decode_utf8(buf, start)
     }
 
-    pub fn read_long(&mut self) -> Result<i64, io::Error> {
-        self.inner.read_i64::<BE>()
+    pub fn check_eof(&mut self) -> Result<bool, Error> {
+        if !self.pending.is_empty() {
+            return Ok(false);
+        }
+        let position = self.position;
+        Ok(self
+            .inner
+            .fill_buf()
+            .map_err(|e| Error::from_io(e, position))?
+            .is_empty())
     }
 
-    pub fn read_utf8(&mut self, len: usize) -> Result<String> {
-        if 0 == len {
-            return Ok(String::new());
+    /// Read `len` bytes, keeping them only in the capture.
+    pub(crate) fn skip(&mut self, len: usize) -> Result<(), Error> {
+        let start = self.position;
+        let mut scratch = [0u8; 8 * 1024];
+        let mut left = len;
+        while left > 0 {
+            let n = left.min(scratch.len());
+            self.fill(&mut scratch[..n])
+                .map_err(|e| Error::from_io(e, start))?;
+            left -= n;
         }
-        let mut buf = vec![0u8; len];
-        self.inner.read_exact(&mut buf)?;
+        Ok(())
+    }
+
+    /// Start recording the bytes read, discarding any previous recording.
+    pub(crate) fn start_capture(&mut self) {
+        let mut capture = self.capture.take().unwrap_or_default();
+        capture.clear();
+        self.capture = Some(capture);
+    }
 
-        // cesu is a superset of utf-8, so try that first
-        let buf = match String::from_utf8(buf) {
-            Ok(s) => return Ok(s),
-            Err(e) => e.into_bytes(),
+    pub(crate) fn take_capture(&mut self) -> Vec<u8> {
+        self.capture.take().unwrap_or_default()
+    }
+
+    pub(crate) fn captured(&self) -> &[u8] {
+        self.capture.as_deref().unwrap_or_default()
+    }
+
+    /// Give back bytes which have already been read, to be read again.
+    pub(crate) fn unread(&mut self, bytes: Vec<u8>) {
+        self.position -= bytes.len() as u64;
+        let mut bytes = VecDeque::from(bytes);
+        bytes.append(&mut self.pending);
+        self.pending = bytes;
+    }
+
+    /// Give back the bytes captured after the first `at`, to be read (and captured) again.
+    pub(crate) fn unread_captured_from(&mut self, at: usize) {
+        let tail = match &mut self.capture {
+            Some(capture) => capture.split_off(at),
+            None => return,
         };
+        self.unread(tail);
+    }
 
-        match cesu8::from_java_cesu8(&buf) {
-            Ok(s) => Ok(s.to_string()),
-            Err(e) => Err(anyhow!(
-                "invalid 'modified' utf-8: {:?}: {:?}",
-                e,
-                String::from_utf8_lossy(&buf)
-            )),
+    /// Whatever is available without blocking for more, or nothing at the end of the stream.
+    /// These bytes are not captured.
+    pub(crate) fn read_available(&mut self) -> Result<Vec<u8>, Error> {
+        if !self.pending.is_empty() {
+            let ret = Vec::from(std::mem::take(&mut self.pending));
+            self.position += ret.len() as u64;
+            return Ok(ret);
         }
+        let position = self.position;
+        let ret = self
+            .inner
+            .fill_buf()
+            .map_err(|e| Error::from_io(e, position))?
+            .to_vec();
+        self.inner.consume(ret.len());
+        self.position += ret.len() as u64;
+        Ok(ret)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let start = self.position;
+        let mut buf = [0u8; N];
+        self.fill(&mut buf).map_err(|e| Error::from_io(e, start))?;
+        Ok(buf)
+    }
+
+    /// `read_exact`, but keeping `position` and the capture up to date, even on failure
+    fn fill(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let mut done = 0;
+        while done < buf.len() {
+            let n = if !self.pending.is_empty() {
+                take_pending(&mut self.pending, &mut buf[done..])
+            } else {
+                let available = match self.inner.fill_buf() {
+                    Ok(available) => available,
+                    Err(e) if io::ErrorKind::Interrupted == e.kind() => continue,
+                    Err(e) => return Err(e),
+                };
+                if available.is_empty() {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                let n = available.len().min(buf.len() - done);
+                buf[done..done + n].copy_from_slice(&available[..n]);
+                self.inner.consume(n);
+                n
+            };
+            if let Some(capture) = &mut self.capture {
+                capture.extend_from_slice(&buf[done..done + n]);
+            }
+            self.position += n as u64;
+            done += n;
+        }
+        Ok(())
+    }
+}
+
+/// As [`DataInput`], but for tokio's [`AsyncBufRead`].
+///
+/// What has been read from the source is held in a [`DataInput`], so the sync code
+/// (e.g. the framing) can also run over it, and be tried again when it runs out.
+#[cfg(feature = "async")]
+pub struct AsyncDataInput<R: AsyncBufRead + Unpin> {
+    source: R,
+    buffered: DataInput<Buffered>,
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncBufRead + Unpin> AsyncDataInput<R> {
+    pub fn new(source: R) -> Self {
+        AsyncDataInput {
+            source,
+            buffered: DataInput::new(Buffered::default()),
+        }
+    }
+
+    /// Bytes consumed so far.
+    pub fn position(&self) -> u64 {
+        self.buffered.position()
+    }
+
+    pub async fn read_byte(&mut self) -> Result<i8, Error> {
+        self.fill_to(1).await?;
+        self.buffered.read_byte()
+    }
+
+    pub async fn read_unsigned_short(&mut self) -> Result<u16, Error> {
+        self.fill_to(2).await?;
+        self.buffered.read_unsigned_short()
+    }
+
+    pub async fn read_int(&mut self) -> Result<i32, Error> {
+        self.fill_to(4).await?;
+        self.buffered.read_int()
+    }
+
+    pub async fn read_long(&mut self) -> Result<i64, Error> {
+        self.fill_to(8).await?;
+        self.buffered.read_long()
+    }
+
+    pub async fn read_utf8(&mut self, len: usize) -> Result<String, Error> {
+        self.fill_to(len).await?;
+        self.buffered.read_utf8(len)
+    }
+
+    pub async fn check_eof(&mut self) -> Result<bool, Error> {
+        self.fill_to(1).await?;
+        self.buffered.check_eof()
+    }
+
+    /// Run `attempt` over what has been read, reading more, and running it again, while
+    /// it runs out with [`io::ErrorKind::WouldBlock`]. When it does, it must have handed
+    /// back (or kept track of) what it read.
+    pub(crate) async fn retry<T>(
+        &mut self,
+        mut attempt: impl FnMut(&mut DataInput<Buffered>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        // doubled each time, so a long field is only read again a few times
+        let mut want = 1;
+        loop {
+            match attempt(&mut self.buffered) {
+                Err(e) if e.is_would_block() => {
+                    self.fill(want).await?;
+                    want = want.saturating_mul(2);
+                }
+                other => return other,
+            }
+        }
+    }
+
+    /// Read until at least `len` bytes are waiting, unless the source ends first.
+    async fn fill_to(&mut self, len: usize) -> Result<(), Error> {
+        let waiting = self.buffered.pending.len() + self.buffered.inner.waiting();
+        if waiting < len {
+            self.fill(len - waiting).await?;
+        }
+        Ok(())
+    }
+
+    /// Move at least `want` bytes from the source into the buffer, unless it ends first.
+    async fn fill(&mut self, want: usize) -> Result<(), Error> {
+        let mut got = 0;
+        while got < want {
+            let available = match self.source.fill_buf().await {
+                Ok(available) => available,
+                Err(e) if io::ErrorKind::Interrupted == e.kind() => continue,
+                Err(e) => return Err(Error::from_io(e, self.buffered.position())),
+            };
+            let n = available.len();
+            if 0 == n {
+                self.buffered.inner.eof = true;
+                break;
+            }
+            self.buffered.inner.extend(available);
+            self.source.consume(n);
+            got += n;
+        }
+        Ok(())
+    }
+}
+
+/// What an [`AsyncDataInput`] has read from its source, but not yet consumed. Running out
+/// before the source has ended is [`io::ErrorKind::WouldBlock`].
+#[cfg(feature = "async")]
+#[derive(Default)]
+pub(crate) struct Buffered {
+    buf: Vec<u8>,
+    /// how much of `buf` has been consumed
+    start: usize,
+    eof: bool,
+}
+
+#[cfg(feature = "async")]
+impl Buffered {
+    fn waiting(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    fn extend(&mut self, data: &[u8]) {
+        self.buf.drain(..self.start);
+        self.start = 0;
+        self.buf.extend_from_slice(data);
+    }
+}
+
+#[cfg(feature = "async")]
+impl io::Read for Buffered {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+impl BufRead for Buffered {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.start == self.buf.len() && !self.eof {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        Ok(&self.buf[self.start..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.start += amt;
+    }
+}
+
+/// How much of a long string to ask for at once.
+const GROWTH: usize = 64 * 1024;
+
+fn take_pending(pending: &mut VecDeque<u8>, buf: &mut [u8]) -> usize {
+    let n = pending.len().min(buf.len());
+    for (to, from) in buf.iter_mut().zip(pending.drain(..n)) {
+        *to = from;
+    }
+    n
+}
+
+pub(crate) fn decode_utf8(buf: Vec<u8>, offset: u64) -> Result<String, Error> {
+    // cesu is a superset of utf-8, so try that first
+    let buf = match String::from_utf8(buf) {
+        Ok(s) => return Ok(s),
+        Err(e) => e.into_bytes(),
+    };
+
+    match cesu8::from_java_cesu8(&buf) {
+        Ok(s) => Ok(s.to_string()),
+        Err(_) => Err(bad_utf8(&buf, offset)),
+    }
+}
+
+/// As `decode_utf8`, but only allocating if the data isn't already utf-8.
+pub(crate) fn decode_str(buf: &[u8], offset: u64) -> Result<Cow<'_, str>, Error> {
+    if let Ok(s) = std::str::from_utf8(buf) {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    cesu8::from_java_cesu8(buf).map_err(|_| bad_utf8(buf, offset))
+}
+
+fn bad_utf8(buf: &[u8], offset: u64) -> Error {
+    Error::BadUtf8 {
+        location: Location {
+            offset,
+            ..Location::default()
+        },
+        lossy: String::from_utf8_lossy(buf).to_string(),
+    }
+}
+
+pub struct DataOutput<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> DataOutput<W> {
+    pub fn new(inner: W) -> Self {
+        DataOutput { inner }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    pub fn write_byte(&mut self, value: i8) -> Result<(), io::Error> {
+        self.inner.write_i8(value)
+    }
+
+    pub fn write_unsigned_short(&mut self, value: u16) -> Result<(), io::Error> {
+        self.inner.write_u16::<BE>(value)
+    }
+
+    pub fn write_int(&mut self, value: i32) -> Result<(), io::Error> {
+        self.inner.write_i32::<BE>(value)
+    }
+
+    pub fn write_long(&mut self, value: i64) -> Result<(), io::Error> {
+        self.inner.write_i64::<BE>(value)
+    }
+
+    /// `DataOutput#writeUTF`: a short length, then 'modified' utf-8
+    pub fn write_utf(&mut self, value: &str) -> Result<()> {
+        let buf = cesu8::to_java_cesu8(value);
+        let len = u16::try_from(buf.len()).with_context(|| anyhow!("string too long for utf"))?;
+        self.write_unsigned_short(len)?;
+        self.inner.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// `IndexDataWriter#writeString`: as `write_utf`, but with an int length
+    pub fn write_string(&mut self, value: &str) -> Result<()> {
+        let buf = cesu8::to_java_cesu8(value);
+        self.write_int(i32::try_from(buf.len()).with_context(|| anyhow!("string too long"))?)?;
+        self.inner.write_all(&buf)?;
+        Ok(())
     }
 
-    pub fn check_eof(&mut self) -> Result<bool> {
-        Ok(self.inner.fill_buf()?.is_empty())
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.inner.flush()
     }
 }
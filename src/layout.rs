@@ -0,0 +1,163 @@
+//! Where artifacts live in a maven2 repository, e.g.
+//! `org/example/example/1.0/example-1.0-sources.jar`.
+
+use std::borrow::Cow;
+
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Result;
+use compact_str::CompactString;
+
+use crate::nexus::FullInfo;
+use crate::nexus::UniqId;
+use crate::version::is_timestamped_snapshot;
+
+/// The artifact's directory, e.g. `org/example/example/1.0`.
+///
+/// Timestamped snapshots, like `1.0-20240101.123456-1`, live in their
+/// `1.0-SNAPSHOT` directory.
+pub fn directory(id: &UniqId) -> String {
+    format!(
+        "{}/{}/{}",
+        id.group.replace('.', "/"),
+        id.artifact,
+        base_version(&id.version)
+    )
+}
+
+/// The artifact's file, e.g. `org/example/example/1.0/example-1.0-sources.jar`.
+pub fn path(id: &UniqId, info: &FullInfo) -> String {
+    let mut ret = format!("{}/{}-{}", directory(id), id.artifact, id.version);
+    if let Some(classifier) = &id.classifier {
+        ret.push('-');
+        ret.push_str(classifier);
+    }
+    ret.push('.');
+    ret.push_str(extension(id, info));
+    ret
+}
+
+/// The artifact's sha1 checksum file, `path` + `.sha1`.
+pub fn sha1_path(id: &UniqId, info: &FullInfo) -> String {
+    path(id, info) + ".sha1"
+}
+
+/// The artifact's signature, `path` + `.asc`.
+pub fn asc_path(id: &UniqId, info: &FullInfo) -> String {
+    path(id, info) + ".asc"
+}
+
+/// The pom for the artifact, which never has a classifier.
+pub fn pom_path(id: &UniqId) -> String {
+    format!("{}/{}-{}.pom", directory(id), id.artifact, id.version)
+}
+
+/// The extension the file has, from the `u` field, or failing that, the `i` field.
+///
+/// Very old records have neither, so it's guessed from the packaging, as Maven's
+/// artifact handlers would, e.g. `maven-plugin`s are `jar`s.
+pub fn extension<'a>(id: &'a UniqId, info: &'a FullInfo) -> &'a str {
+    if let Some(extension) = &id.extension {
+        return extension;
+    }
+    if !info.extension.is_empty() {
+        return &info.extension;
+    }
+    match info.packaging.as_str() {
+        "" | "maven-plugin" | "bundle" | "ejb" | "ejb-client" | "test-jar" | "java-source"
+        | "javadoc" => "jar",
+        packaging => packaging,
+    }
+}
+
+/// The artifact a path is for; the inverse of [`path`].
+///
+/// For a timestamped snapshot, the version is the file's, e.g. `1.0-20240101.123456-1`,
+/// not the directory's. Suffixes like `.sha1` are kept as part of the extension.
+pub fn parse_path(path: &str) -> Result<UniqId> {
+    let parts = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    let (group, artifact, dir_version, file) = match parts[..] {
+        [ref group @ .., artifact, version, file] if !group.is_empty() => {
+            (group.join("."), artifact, version, file)
+        }
+        _ => bail!("expected group/artifact/version/file: {path:?}"),
+    };
+    ensure!(
+        !group.split('.').any(str::is_empty) && !artifact.is_empty() && !dir_version.is_empty(),
+        "empty path component: {path:?}"
+    );
+
+    let rest = match file
+        .strip_prefix(artifact)
+        .and_then(|rest| rest.strip_prefix('-'))
+    {
+        Some(rest) => rest,
+        None => bail!("file name doesn't start with {artifact:?}: {path:?}"),
+    };
+
+    let version = match file_version(rest, dir_version) {
+        Some(version) => version,
+        None => bail!("file name doesn't match version {dir_version:?}: {path:?}"),
+    };
+    let rest = &rest[version.len()..];
+
+    let (classifier, extension) = match rest.split_once('.') {
+        Some(("", extension)) => (None, extension),
+        Some((classifier, extension)) => match classifier.strip_prefix('-') {
+            Some(classifier) if !classifier.is_empty() => (Some(classifier), extension),
+            _ => bail!("unexpected text after the version: {path:?}"),
+        },
+        None => bail!("no extension: {path:?}"),
+    };
+    ensure!(!extension.is_empty(), "no extension: {path:?}");
+
+    Ok(UniqId {
+        group: group.into(),
+        artifact: artifact.into(),
+        version: version.into(),
+        classifier: classifier.map(CompactString::from),
+        extension: Some(extension.into()),
+    })
+}
+
+/// `1.0-SNAPSHOT` for `1.0-20240101.123456-1`, anything else as-is.
+fn base_version(version: &str) -> Cow<'_, str> {
+    if !is_timestamped_snapshot(version) {
+        return Cow::Borrowed(version);
+    }
+    let mut parts = version.rsplitn(3, '-');
+    let base = parts.nth(2).expect("checked by is_timestamped_snapshot");
+    Cow::Owned(format!("{base}-SNAPSHOT"))
+}
+
+/// The version at the start of `file`, which is either the directory's version,
+/// or, in a `-SNAPSHOT` directory, a timestamped snapshot of it.
+fn file_version<'f>(file: &'f str, dir_version: &str) -> Option<&'f str> {
+    let timestamped = dir_version
+        .strip_suffix("SNAPSHOT")
+        .filter(|stem| file.starts_with(stem))
+        .and_then(|stem| {
+            // `20240101.123456-1`, then the classifier or extension
+            let build = stem.len() + "20240101.123456-".len();
+            let end = file
+                .get(build..)?
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(file.len(), |end| build + end);
+            Some(&file[..end])
+        })
+        .filter(|version| is_timestamped_snapshot(version));
+    if timestamped.is_some() {
+        return timestamped;
+    }
+
+    // `bar-1.0.1.jar` is a different version, not `1.0` with an extension of `1.jar`
+    let rest = file.strip_prefix(dir_version)?;
+    let longer = rest
+        .strip_prefix('.')
+        .and_then(|extension| extension.split(['.', '-']).next())
+        .is_some_and(|part| part.bytes().all(|b| b.is_ascii_digit()));
+    match rest.chars().next() {
+        Some('.' | '-') if !longer => Some(&file[..dir_version.len()]),
+        _ => None,
+    }
+}
@@ -1,6 +1,17 @@
-mod java;
+mod error;
+mod time;
 
+pub mod compression;
+pub mod identify;
+pub mod incremental;
+pub mod java;
+pub mod layout;
 pub mod nexus;
+pub mod purl;
+pub mod version;
 
 #[cfg(feature = "db")]
 pub mod db;
+
+pub use crate::error::Error;
+pub use crate::error::Location;
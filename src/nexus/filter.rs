@@ -0,0 +1,91 @@
+use std::io::BufRead;
+
+use super::record::split_uniq;
+use super::Event;
+use super::IndexReader;
+use super::RawRecord;
+use super::UniqIdRef;
+use crate::Error;
+
+/// An [`IndexReader`] which only decodes the records a filter on the `u` field wants;
+/// see [`IndexReader::filter_uniq`].
+pub struct Filtered<R: BufRead, F> {
+    reader: IndexReader<R>,
+    filter: F,
+    done: bool,
+}
+
+impl<R: BufRead> IndexReader<R> {
+    /// Only decode docs (and deletions) whose raw `u` (or `del`) value, e.g.
+    /// `org.example|example|1.0|NA|jar`, passes `filter`.
+    ///
+    /// Everything else is skipped, including records with neither field, e.g. the descriptor;
+    /// errors in the framing are still reported.
+    pub fn filter_uniq_raw<F: FnMut(&str) -> bool>(self, filter: F) -> Filtered<R, F> {
+        Filtered {
+            reader: self,
+            filter,
+            done: false,
+        }
+    }
+
+    /// As [`filter_uniq_raw`](Self::filter_uniq_raw), but on the parsed value.
+    /// Records where the value can't be parsed are kept, so the error is reported.
+    pub fn filter_uniq<F: FnMut(&UniqIdRef) -> bool>(
+        self,
+        mut filter: F,
+    ) -> Filtered<R, impl FnMut(&str) -> bool> {
+        self.filter_uniq_raw(move |raw| split_uniq(raw).map_or(true, |id| filter(&id)))
+    }
+}
+
+impl<R: BufRead, F: FnMut(&str) -> bool> Iterator for Filtered<R, F> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Result<Event, Error>> {
+        while !self.done {
+            let resync = self.reader.framer.options.resync;
+            let record = match self.reader.next_record() {
+                Ok(Some(record)) => record,
+                Ok(None) => break,
+                Err(error @ Error::Skipped { .. }) => {
+                    return Some(Ok(Event::Error {
+                        error,
+                        raw: Vec::new(),
+                    }))
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            if !wanted(&record, &mut self.filter) {
+                continue;
+            }
+
+            let event = record.decode_event(resync);
+            self.done = event.is_err();
+            return Some(event);
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+fn wanted(record: &RawRecord, filter: &mut impl FnMut(&str) -> bool) -> bool {
+    let field = match record
+        .raw_fields()
+        .position(|(name, _)| b"u" == name || b"del" == name)
+    {
+        Some(field) => field,
+        None => return false,
+    };
+
+    match record.value(field) {
+        Ok(value) => filter(&value),
+        // let the decoding report it
+        Err(_) => true,
+    }
+}
@@ -0,0 +1,345 @@
+use std::io::BufRead;
+use std::ops::Range;
+
+use super::read_flags;
+use super::resync::scan;
+use super::resync::Scan;
+use super::skipped;
+use super::zero_length_name;
+use super::Event;
+use super::RawRecord;
+use super::ReaderOptions;
+use crate::java::DataInput;
+use crate::Error;
+use crate::Location;
+
+/// The name and value of a field, as offsets into the record.
+pub(super) type Span = (Range<usize>, Range<usize>);
+
+/// A record which has been split into fields, but not decoded.
+///
+/// Framing has to happen in order, but is cheap; [`Frame::decode`] is the expensive part,
+/// and can happen anywhere, e.g. on another thread.
+#[derive(Debug)]
+pub struct Frame(Inner);
+
+#[derive(Debug)]
+enum Inner {
+    Record {
+        location: Location,
+        /// the record, exactly as read
+        buf: Vec<u8>,
+        fields: Vec<Span>,
+        resync: bool,
+    },
+    /// The framing failed, and the reader resynchronised; this is the [`Error::Skipped`].
+    Skipped(Error),
+}
+
+impl Frame {
+    pub(super) fn record(
+        location: Location,
+        buf: Vec<u8>,
+        fields: Vec<Span>,
+        resync: bool,
+    ) -> Frame {
+        Frame(Inner::Record {
+            location,
+            buf,
+            fields,
+            resync,
+        })
+    }
+
+    pub(super) fn skipped(error: Error) -> Frame {
+        Frame(Inner::Skipped(error))
+    }
+
+    /// Where the record starts.
+    pub fn location(&self) -> Location {
+        match &self.0 {
+            Inner::Record { location, .. } => *location,
+            Inner::Skipped(error) => *error.location(),
+        }
+    }
+
+    /// As [`IndexReader`](super::IndexReader) would produce it; an `Err` is unrecoverable,
+    /// as with the reader.
+    pub fn decode(self) -> Result<Event, Error> {
+        let (location, buf, fields, resync) = match self.0 {
+            Inner::Record {
+                location,
+                buf,
+                fields,
+                resync,
+            } => (location, buf, fields, resync),
+            Inner::Skipped(error) => {
+                return Ok(Event::Error {
+                    error,
+                    raw: Vec::new(),
+                })
+            }
+        };
+
+        RawRecord::new(location, &buf, &fields).decode_event(resync)
+    }
+}
+
+/// Splits records out of a [`DataInput`], resynchronising after broken ones if asked;
+/// shared by the sync and async readers.
+///
+/// If the input runs dry with [`io::ErrorKind::WouldBlock`](std::io::ErrorKind::WouldBlock),
+/// the progress is kept: the partial field is handed back to the input, and the complete
+/// ones stay in `spans` (or, part way through a resync, the window is kept), so the call
+/// can be repeated once there's more, carrying on from there.
+pub(super) struct Framer {
+    pub(super) options: ReaderOptions,
+    /// the fields of the record most recently read
+    pub(super) spans: Vec<Span>,
+    pub(super) records: u64,
+    partial: Option<Partial>,
+    resyncing: Option<Resync>,
+}
+
+/// A record which ran out of input after its field count; the fields so far are in
+/// `spans`, and their bytes in the capture.
+struct Partial {
+    location: Location,
+    field_count: usize,
+    /// of the record's size limit
+    remaining: usize,
+}
+
+/// A resync which ran out of input.
+struct Resync {
+    cause: Error,
+    location: Location,
+    window: Vec<u8>,
+    window_start: u64,
+}
+
+pub(super) enum Framed {
+    /// starting here
+    Record(Location),
+    /// an [`Error::Skipped`], after resynchronising
+    Skipped(Error),
+}
+
+impl Framer {
+    pub(super) fn new(options: ReaderOptions) -> Framer {
+        Framer {
+            options,
+            spans: Vec::new(),
+            records: 0,
+            partial: None,
+            resyncing: None,
+        }
+    }
+
+    pub(super) fn next_frame<R: BufRead>(
+        &mut self,
+        from: &mut DataInput<R>,
+    ) -> Result<Option<Frame>, Error> {
+        Ok(match self.read_next(from)? {
+            Some(Framed::Record(location)) => Some(Frame::record(
+                location,
+                from.take_capture(),
+                std::mem::take(&mut self.spans),
+                self.options.resync,
+            )),
+            Some(Framed::Skipped(error)) => Some(Frame::skipped(error)),
+            None => None,
+        })
+    }
+
+    /// Leaves the record in the capture, and its fields in `spans`.
+    pub(super) fn read_next<R: BufRead>(
+        &mut self,
+        from: &mut DataInput<R>,
+    ) -> Result<Option<Framed>, Error> {
+        if let Some(resync) = self.resyncing.take() {
+            return Ok(Some(Framed::Skipped(self.resync(from, resync)?)));
+        }
+
+        let mut partial = match self.partial.take() {
+            Some(partial) => partial,
+            None => {
+                let location = Location {
+                    record: Some(self.records),
+                    field: None,
+                    offset: from.position(),
+                };
+                from.start_capture();
+                self.spans.clear();
+                match read_field_count(from, &self.options) {
+                    Ok(Some(field_count)) => Partial {
+                        location,
+                        field_count,
+                        remaining: self.options.max_record_size,
+                    },
+                    Ok(None) => return Ok(None),
+                    Err(e) if e.is_would_block() => {
+                        // nothing worth keeping; the record is started again, when there's more
+                        let partial = from.take_capture();
+                        from.unread(partial);
+                        return Err(e);
+                    }
+                    Err(e) => return self.failed(from, e, location),
+                }
+            }
+        };
+
+        match read_fields(from, &self.options, &mut self.spans, &mut partial) {
+            Ok(()) => (),
+            Err(e) if e.is_would_block() => {
+                self.partial = Some(partial);
+                return Err(e);
+            }
+            Err(e) => return self.failed(from, e, partial.location),
+        }
+
+        self.records += 1;
+        Ok(Some(Framed::Record(partial.location)))
+    }
+
+    /// The record starting at `location` is broken; resync past it, if asked to.
+    fn failed<R: BufRead>(
+        &mut self,
+        from: &mut DataInput<R>,
+        e: Error,
+        location: Location,
+    ) -> Result<Option<Framed>, Error> {
+        if !(self.options.resync && e.is_framing()) {
+            return Err(e.in_record(self.records));
+        }
+
+        let cause = e.in_record(self.records);
+        self.records += 1;
+        let mut window = from.take_capture();
+        // the record doesn't start where it claimed to, so don't look there again
+        window.drain(..window.len().min(1));
+        let resync = Resync {
+            cause,
+            location,
+            window,
+            window_start: location.offset + 1,
+        };
+        Ok(Some(Framed::Skipped(self.resync(from, resync)?)))
+    }
+
+    /// Skip forward from the start of the broken record to the next plausible one.
+    fn resync<R: BufRead>(
+        &mut self,
+        from: &mut DataInput<R>,
+        mut state: Resync,
+    ) -> Result<Error, Error> {
+        let mut eof = false;
+
+        let end = loop {
+            let window = &mut state.window;
+            match scan(window, &self.options, eof) {
+                Scan::Found(start) => {
+                    from.unread(window.split_off(start));
+                    break state.window_start + start as u64;
+                }
+                Scan::NeedMore(start) => {
+                    window.drain(..start);
+                    state.window_start += start as u64;
+                }
+                Scan::NotFound if eof => break state.window_start + window.len() as u64,
+                Scan::NotFound => {
+                    state.window_start += window.len() as u64;
+                    window.clear();
+                }
+            }
+
+            let more = match from.read_available() {
+                Err(e) if e.is_would_block() => {
+                    self.resyncing = Some(state);
+                    return Err(e);
+                }
+                more => more?,
+            };
+            eof = more.is_empty();
+            state.window.extend_from_slice(&more);
+        };
+
+        Ok(skipped(state.cause, state.location, end))
+    }
+}
+
+/// The field count of the next record, leaving it in the `DataInput`'s capture;
+/// `None` at the end of the stream.
+fn read_field_count<R: BufRead>(
+    f: &mut DataInput<R>,
+    options: &ReaderOptions,
+) -> Result<Option<usize>, Error> {
+    if f.check_eof()? {
+        return Ok(None);
+    }
+
+    let location = Location {
+        offset: f.position(),
+        ..Location::default()
+    };
+    Ok(Some(options.check_field_count(f.read_int()?, location)?))
+}
+
+/// Read the rest of the record's fields into `fields`, leaving the bytes in the
+/// `DataInput`'s capture. If the input runs dry, the partial field is handed back to it.
+fn read_fields<R: BufRead>(
+    f: &mut DataInput<R>,
+    options: &ReaderOptions,
+    fields: &mut Vec<Span>,
+    partial: &mut Partial,
+) -> Result<(), Error> {
+    while fields.len() < partial.field_count {
+        let field = fields.len();
+        let field_start = f.captured().len();
+        let mut remaining = partial.remaining;
+        match read_field(f, options, &mut remaining, partial.location.offset) {
+            Ok(span) => fields.push(span),
+            Err(e) if e.is_would_block() => {
+                f.unread_captured_from(field_start);
+                return Err(e);
+            }
+            Err(e) => return Err(e.in_field(field)),
+        }
+        partial.remaining = remaining;
+    }
+
+    Ok(())
+}
+
+fn read_field<R: BufRead>(
+    f: &mut DataInput<R>,
+    options: &ReaderOptions,
+    remaining: &mut usize,
+    start: u64,
+) -> Result<Span, Error> {
+    let at = |f: &DataInput<R>| Location {
+        offset: f.position(),
+        ..Location::default()
+    };
+    let relative = |f: &DataInput<R>| (f.position() - start) as usize;
+
+    read_flags(f.read_byte()?, at(f))?;
+
+    let location = at(f);
+    let name_len = options.take_name(f.read_unsigned_short()?, remaining, location)?;
+    if 0 == name_len {
+        return Err(zero_length_name(location));
+    }
+    let name = relative(f);
+    f.skip(name_len)?;
+    let name = name..relative(f);
+
+    // yup, they went out of their way to use signed data here
+    let location = at(f);
+    let value_len = options.take_value(f.read_int()?, remaining, location)?;
+    let value = relative(f);
+    f.skip(value_len)?;
+    let value = value..relative(f);
+
+    Ok((name, value))
+}
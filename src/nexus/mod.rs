@@ -0,0 +1,794 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Result;
+use bitflags::bitflags;
+use compact_str::{CompactString, ToCompactString};
+use hex;
+
+use self::frame::Framed;
+use self::frame::Framer;
+use self::record::split_uniq;
+use crate::java::DataInput;
+use crate::Error;
+use crate::Location;
+
+mod filter;
+mod frame;
+mod osgi;
+mod parallel;
+mod record;
+mod resync;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "async")]
+mod stream;
+mod writer;
+
+pub use self::filter::Filtered;
+pub use self::frame::Frame;
+pub use self::osgi::Osgi;
+pub use self::parallel::read_parallel;
+pub use self::parallel::ParallelReader;
+pub use self::record::DocRef;
+pub use self::record::RawRecord;
+pub use self::record::UniqIdRef;
+#[cfg(feature = "async")]
+pub use self::stream::read_stream;
+#[cfg(feature = "async")]
+pub use self::stream::AsyncIndexReader;
+pub use self::writer::Writer;
+
+pub type Checksum = [u8; 20];
+
+/// Identifies an artifact.
+///
+/// Displays in the colon form users type, `group:artifact:version[:classifier][@extension]`,
+/// or, with `{:#}`, the pipe form of the `u` field: `group|artifact|version|classifier[|extension]`,
+/// with `NA` for no classifier. Either form can be parsed.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UniqId {
+    pub group: CompactString,
+    pub artifact: CompactString,
+    pub version: CompactString,
+    pub classifier: Option<CompactString>,
+    pub extension: Option<CompactString>,
+}
+
+impl fmt::Display for UniqId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(
+                f,
+                "{}|{}|{}|{}",
+                self.group,
+                self.artifact,
+                self.version,
+                self.classifier.as_deref().unwrap_or("NA")
+            )?;
+            if let Some(extension) = &self.extension {
+                write!(f, "|{extension}")?;
+            }
+            return Ok(());
+        }
+
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{classifier}")?;
+        }
+        if let Some(extension) = &self.extension {
+            write!(f, "@{extension}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for UniqId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('|') {
+            return read_uniq(s).map_err(|reason| anyhow!("{reason}: {s:?}"));
+        }
+
+        let (coords, extension) = match s.rsplit_once('@') {
+            Some((coords, extension)) => (coords, Some(extension)),
+            None => (s, None),
+        };
+        let parts = coords.split(':').collect::<Vec<_>>();
+        let (group, artifact, version, classifier) = match parts[..] {
+            [group, artifact, version] => (group, artifact, version, None),
+            [group, artifact, version, classifier] => (group, artifact, version, Some(classifier)),
+            _ => bail!("expected group:artifact:version[:classifier][@extension]: {s:?}"),
+        };
+        ensure!(
+            ![group, artifact, version].contains(&"")
+                && Some("") != classifier
+                && Some("") != extension,
+            "empty coordinate: {s:?}"
+        );
+
+        Ok(UniqId {
+            group: group.into(),
+            artifact: artifact.into(),
+            version: version.into(),
+            classifier: classifier.map(CompactString::from),
+            extension: extension.map(CompactString::from),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FullInfo {
+    pub packaging: CompactString,
+    #[cfg_attr(feature = "serde", serde(with = "serialization::timestamp"))]
+    pub last_modified: u64,
+    pub size: Option<u64>,
+    pub source_attached: AttachmentStatus,
+    pub javadoc_attached: AttachmentStatus,
+    pub signature_attached: AttachmentStatus,
+    pub extension: CompactString,
+}
+
+/// An artifact in the index.
+///
+/// With the `serde` feature, this is serialised with the field names as they are here;
+/// `None`s are `null`, and may be omitted when deserialising. The exceptions are:
+///  * timestamps (`modified` and `object_info.last_modified`) are objects,
+///    `{"millis": 1318434018114, "rfc3339": "2011-10-12T15:40:18.114Z"}`, or just the millis,
+///  * `checksum` is a lower-case hex string,
+///  * [`AttachmentStatus`]es are `"absent"`, `"present"` or `"unavailable"`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Doc {
+    pub id: UniqId,
+    pub object_info: FullInfo,
+    #[cfg_attr(feature = "serde", serde(with = "serialization::timestamp"))]
+    pub modified: u64,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "serialization::checksum"))]
+    pub checksum: Option<Checksum>,
+    /// `classNames`: the classes in the jar, as stored, e.g. `/org/example/Foo`
+    pub class_names: Option<Vec<String>>,
+    pub plugin: Option<Plugin>,
+    pub osgi: Option<Osgi>,
+    /// any other fields, by name
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: BTreeMap<CompactString, String>,
+}
+
+/// For artifacts with `maven-plugin` packaging.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plugin {
+    /// `px`: the prefix used on the command line, e.g. `compiler` in `compiler:compile`
+    pub prefix: Option<String>,
+    /// `gx`: the goals the plugin provides
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub goals: Vec<String>,
+}
+
+/// With the `serde` feature, events are objects with a `type` of `doc`, `delete`,
+/// `descriptor`, `groups` or `error`, alongside the fields of the [`Doc`], the deleted
+/// [`UniqId`], or the variant. Errors are serialised as their message, and can't be
+/// deserialised.
+// almost every event is a `Doc`, so boxing it would just add an allocation
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum Event {
+    Doc(Doc),
+    Delete(UniqId),
+    /// The `DESCRIPTOR`/`IDXINFO` record, normally the first in the index.
+    Descriptor {
+        /// the index format version, e.g. `1.0`
+        version: String,
+        repository_id: String,
+    },
+    /// A complete list of groups, normally at the end of the index.
+    Groups {
+        kind: GroupKind,
+        groups: Vec<CompactString>,
+    },
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Error {
+        #[cfg_attr(
+            feature = "serde",
+            serde(serialize_with = "serialization::error_message")
+        )]
+        error: Error,
+        raw: Vec<(Name, String)>,
+    },
+}
+
+pub fn read<R: BufRead, F>(from: R, mut cb: F) -> Result<()>
+where
+    F: FnMut(Event) -> Result<()>,
+{
+    for event in IndexReader::new(from)? {
+        cb(event?)?;
+    }
+
+    Ok(())
+}
+
+/// As [`read`], but first decompressing the input, if necessary;
+/// see [`decompress`](crate::compression::decompress).
+pub fn read_any<R: BufRead, F>(from: R, cb: F) -> Result<()>
+where
+    F: FnMut(Event) -> Result<()>,
+{
+    read(crate::compression::decompress(from)?, cb)
+}
+
+/// Limits on what a single record may contain, so a corrupt or hostile index
+/// fails early, instead of first asking for gigabytes of memory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReaderOptions {
+    pub max_fields: usize,
+    /// in bytes, as encoded
+    pub max_name_len: usize,
+    /// in bytes, as encoded
+    pub max_value_len: usize,
+    /// the total length of the names and values in a record
+    pub max_record_size: usize,
+    /// After a framing error, skip forward to the next thing which looks like a record,
+    /// reporting an [`Error::Skipped`] event, instead of ending the stream.
+    pub resync: bool,
+}
+
+impl Default for ReaderOptions {
+    /// Comfortably larger than anything on Central; `classNames` values reach a few megabytes.
+    fn default() -> Self {
+        ReaderOptions {
+            max_fields: 1_024,
+            max_name_len: 1_024,
+            max_value_len: 64 * 1_024 * 1_024,
+            max_record_size: 128 * 1_024 * 1_024,
+            resync: false,
+        }
+    }
+}
+
+impl ReaderOptions {
+    fn check_field_count(&self, count: i32, location: Location) -> Result<usize, Error> {
+        let count = usize::try_from(count).map_err(|_| Error::BadLength {
+            location,
+            what: "field count",
+            len: i64::from(count),
+        })?;
+        check_limit(count, self.max_fields, "field count", location)
+    }
+
+    fn take_name(
+        &self,
+        len: u16,
+        remaining: &mut usize,
+        location: Location,
+    ) -> Result<usize, Error> {
+        let len = check_limit(usize::from(len), self.max_name_len, "field name", location)?;
+        take_record(len, remaining, location)
+    }
+
+    fn take_value(
+        &self,
+        len: i32,
+        remaining: &mut usize,
+        location: Location,
+    ) -> Result<usize, Error> {
+        let len = usize::try_from(len).map_err(|_| Error::BadLength {
+            location,
+            what: "field value length",
+            len: i64::from(len),
+        })?;
+        let len = check_limit(len, self.max_value_len, "field value", location)?;
+        take_record(len, remaining, location)
+    }
+}
+
+fn check_limit(
+    len: usize,
+    limit: usize,
+    what: &'static str,
+    location: Location,
+) -> Result<usize, Error> {
+    if len > limit {
+        return Err(Error::LimitExceeded {
+            location,
+            what,
+            len,
+            limit,
+        });
+    }
+    Ok(len)
+}
+
+fn take_record(len: usize, remaining: &mut usize, location: Location) -> Result<usize, Error> {
+    *remaining = remaining.checked_sub(len).ok_or(Error::LimitExceeded {
+        location,
+        what: "record",
+        len,
+        limit: *remaining,
+    })?;
+    Ok(len)
+}
+
+/// Reads an index as an [`Iterator`] of [`Event`]s.
+///
+/// Errors in a single record are reported as [`Event::Error`]; an `Err` means the
+/// stream itself is unreadable, and is the last item produced.
+pub struct IndexReader<R: BufRead> {
+    from: DataInput<R>,
+    framer: Framer,
+    version: i8,
+    timestamp_ms: i64,
+    done: bool,
+}
+
+impl<R: BufRead> IndexReader<R> {
+    /// Reads the header immediately.
+    pub fn new(from: R) -> Result<IndexReader<R>, Error> {
+        IndexReader::with_options(from, ReaderOptions::default())
+    }
+
+    pub fn with_options(from: R, options: ReaderOptions) -> Result<IndexReader<R>, Error> {
+        let mut from = DataInput::new(from);
+
+        let (version, timestamp_ms) = read_header(&mut from)?;
+
+        Ok(IndexReader {
+            from,
+            framer: Framer::new(options),
+            version,
+            timestamp_ms,
+            done: false,
+        })
+    }
+
+    /// The format version from the header; currently always `1`.
+    pub fn version(&self) -> i8 {
+        self.version
+    }
+
+    /// When the index was published, in milliseconds since the epoch, or `-1` if unknown.
+    pub fn timestamp_ms(&self) -> i64 {
+        self.timestamp_ms
+    }
+
+    /// Records read so far, including the ones which didn't produce an event.
+    pub fn records(&self) -> u64 {
+        self.framer.records
+    }
+
+    /// The next record, split into fields but not decoded, or `None` at the end of the stream.
+    ///
+    /// This is what the [`Iterator`] does before calling [`Frame::decode`]; mixing the two
+    /// is fine.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, Error> {
+        self.framer.next_frame(&mut self.from)
+    }
+
+    /// The next record, borrowed from a buffer which is reused for each record,
+    /// or `None` at the end of the stream.
+    ///
+    /// With [`ReaderOptions::resync`], an [`Error::Skipped`] is not the end of the stream,
+    /// and reading can continue.
+    pub fn next_record(&mut self) -> Result<Option<RawRecord<'_>>, Error> {
+        Ok(match self.framer.read_next(&mut self.from)? {
+            Some(Framed::Record(location)) => Some(RawRecord::new(
+                location,
+                self.from.captured(),
+                &self.framer.spans,
+            )),
+            Some(Framed::Skipped(error)) => return Err(error),
+            None => None,
+        })
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>, Error> {
+        self.next_frame()?.map(Frame::decode).transpose()
+    }
+}
+
+impl<R: BufRead> Iterator for IndexReader<R> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Result<Event, Error>> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn skipped(cause: Error, location: Location, end: u64) -> Error {
+    Error::Skipped {
+        location,
+        skipped: location.offset..end,
+        cause: Box::new(cause),
+    }
+}
+
+/// The format version, and the timestamp.
+fn read_header<R: BufRead>(from: &mut DataInput<R>) -> Result<(i8, i64), Error> {
+    let version = read_version(from.read_byte()?)?;
+    Ok((version, from.read_long()?))
+}
+
+fn read_version(version: i8) -> Result<i8, Error> {
+    if 1 != version {
+        return Err(Error::BadHeader {
+            location: Location::default(),
+            version,
+        });
+    }
+    Ok(version)
+}
+
+/// `location` is the start of the record.
+fn to_event(fields: Vec<(Name, String)>, location: Location) -> Event {
+    let at = |field: usize| Location {
+        field: Some(field),
+        ..location
+    };
+
+    if let Some((field, del)) = fields
+        .iter()
+        .enumerate()
+        .find(|(_, (key, _))| key.is_other_eq("del"))
+    {
+        return match read_uniq(&del.1) {
+            Ok(id) => Event::Delete(id),
+            Err(reason) => Event::Error {
+                error: Error::MalformedUniq {
+                    location: at(field),
+                    value: del.1.clone(),
+                    reason,
+                },
+                raw: fields,
+            },
+        };
+    }
+
+    if fields.len() == 2 {
+        let has = |s: &'static str| fields.iter().any(|(name, _)| name.is_other_eq(s));
+        let get = |s: &'static str| {
+            fields
+                .iter()
+                .enumerate()
+                .find(|(_, (name, _))| name.is_other_eq(s))
+                .map(|(field, (_, value))| (field, value))
+        };
+
+        if has("DESCRIPTOR") {
+            if let Some((field, info)) = get("IDXINFO") {
+                return match info.split_once('|') {
+                    Some((version, repository_id)) => Event::Descriptor {
+                        version: version.to_string(),
+                        repository_id: repository_id.to_string(),
+                    },
+                    None => Event::Error {
+                        error: Error::MalformedDescriptor {
+                            location: at(field),
+                            value: info.to_string(),
+                        },
+                        raw: fields,
+                    },
+                };
+            }
+        }
+
+        for kind in [GroupKind::Root, GroupKind::All] {
+            if has(kind.marker()) {
+                if let Some((_, list)) = get(kind.list_field()) {
+                    return Event::Groups {
+                        kind,
+                        groups: read_groups(list),
+                    };
+                }
+            }
+        }
+    }
+
+    let has = |name: &Name| fields.iter().any(|(key, _)| key == name);
+    if !(has(&Name::U) && has(&Name::I) && has(&Name::M)) {
+        return Event::Error {
+            error: Error::UnrecognisedRecord { location },
+            raw: fields,
+        };
+    }
+
+    match read_doc(&fields, location) {
+        Ok(doc) => Event::Doc(doc),
+        Err(error) => Event::Error { error, raw: fields },
+    }
+}
+
+fn read_doc(fields: &[(Name, String)], location: Location) -> Result<Doc, Error> {
+    let at = |field: usize| Location {
+        field: Some(field),
+        ..location
+    };
+
+    let mut you = None;
+    let mut eye = None;
+    let mut modified = None;
+    let mut name = None;
+    let mut description = None;
+    let mut checksum = None;
+    let mut class_names = None;
+    let mut plugin = None;
+    let mut osgi = Osgi::default();
+    let mut extra = BTreeMap::new();
+
+    for (field, (field_name, value)) in fields.iter().enumerate() {
+        match field_name {
+            Name::U => {
+                you = Some(read_uniq(value).map_err(|reason| Error::MalformedUniq {
+                    location: at(field),
+                    value: value.to_string(),
+                    reason,
+                })?)
+            }
+            Name::I => {
+                eye = Some(read_info(value).map_err(|reason| Error::MalformedInfo {
+                    location: at(field),
+                    value: value.to_string(),
+                    reason,
+                })?)
+            }
+            Name::M => {
+                modified = Some(value.parse::<u64>().map_err(|_| Error::MalformedModified {
+                    location: at(field),
+                    value: value.to_string(),
+                })?)
+            }
+            Name::N => name = Some(value.to_string()),
+            Name::D => description = Some(value.to_string()),
+            // there are junk checksums out there, which aren't worth losing the doc over
+            Name::Checksum => checksum = read_checksum(value),
+            Name::ClassNames => {
+                class_names = Some(
+                    value
+                        .split('\n')
+                        .filter(|class| !class.is_empty())
+                        .map(|class| class.to_string())
+                        .collect(),
+                )
+            }
+            Name::PluginPrefix => {
+                plugin.get_or_insert_with(Plugin::default).prefix = Some(value.to_string())
+            }
+            Name::PluginGoals => {
+                plugin.get_or_insert_with(Plugin::default).goals = read_list(value)
+            }
+            Name::Other(other) => match osgi.header_mut(other) {
+                Some(header) => *header = Some(value.to_string()),
+                None => {
+                    extra.insert(other.clone(), value.to_string());
+                }
+            },
+        }
+    }
+
+    let missing = |name| Error::MissingField { location, name };
+
+    Ok(Doc {
+        id: you.ok_or_else(|| missing("u"))?,
+        object_info: eye.ok_or_else(|| missing("i"))?,
+        modified: modified.ok_or_else(|| missing("m"))?,
+        name,
+        description,
+        checksum,
+        class_names,
+        plugin,
+        osgi: Some(osgi).filter(|osgi| osgi != &Osgi::default()),
+        extra,
+    })
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Name {
+    U,
+    I,
+    N,
+    D,
+    M,
+    Checksum,
+    ClassNames,
+    PluginPrefix,
+    PluginGoals,
+    Other(CompactString),
+}
+
+impl Name {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Name::U => "u",
+            Name::I => "i",
+            Name::N => "n",
+            Name::D => "d",
+            Name::M => "m",
+            Name::Checksum => "1",
+            Name::ClassNames => "classNames",
+            Name::PluginPrefix => "px",
+            Name::PluginGoals => "gx",
+            Name::Other(s) => s.as_str(),
+        }
+    }
+
+    fn from_byte(name: u8) -> Name {
+        match name {
+            b'u' => Name::U,
+            b'i' => Name::I,
+            b'n' => Name::N,
+            b'm' => Name::M,
+            b'd' => Name::D,
+            b'1' => Name::Checksum,
+            // there are currently no hits for this:
+            other => Name::Other(char::from(other).to_compact_string()),
+        }
+    }
+
+    fn from_string(name: CompactString) -> Name {
+        match name.as_str() {
+            "classNames" => Name::ClassNames,
+            "px" => Name::PluginPrefix,
+            "gx" => Name::PluginGoals,
+            _ => Name::Other(name),
+        }
+    }
+
+    fn is_other_eq(&self, other: &str) -> bool {
+        match self {
+            Name::Other(s) => s.as_str() == other,
+            _ => false,
+        }
+    }
+}
+
+/// `location` is just after the flags byte
+fn read_flags(flags: i8, location: Location) -> Result<FieldFlag, Error> {
+    let flags = flags as u8;
+    FieldFlag::from_bits(flags).ok_or(Error::BadFieldFlags {
+        location: Location {
+            offset: location.offset - 1,
+            ..location
+        },
+        flags,
+    })
+}
+
+fn zero_length_name(location: Location) -> Error {
+    Error::BadLength {
+        location,
+        what: "field name length",
+        len: 0,
+    }
+}
+
+/// `None` for anything but 40 hex digits.
+#[inline]
+fn read_checksum(value: &str) -> Option<Checksum> {
+    let mut arr = [0u8; 20];
+    hex::decode_to_slice(value, &mut arr).ok()?;
+    Some(arr)
+}
+
+fn read_uniq(value: &str) -> Result<UniqId, &'static str> {
+    Ok(split_uniq(value)?.to_uniq_id())
+}
+
+fn read_info(value: &str) -> Result<FullInfo, &'static str> {
+    let mut parts = value.split('|');
+    Ok(FullInfo {
+        packaging: parts.next().ok_or("short info: packaging")?.into(),
+        last_modified: parts
+            .next()
+            .ok_or("short info: time")?
+            .parse::<u64>()
+            .map_err(|_| "reading time")?,
+        size: read_size(parts.next().ok_or("short i: size")?)?,
+        source_attached: AttachmentStatus::read(parts.next().ok_or("short info: sources flag")?)?,
+        javadoc_attached: AttachmentStatus::read(parts.next().ok_or("short info: flag 2")?)?,
+        signature_attached: AttachmentStatus::read(parts.next().ok_or("short info: flag 3")?)?,
+        extension: parts.next().ok_or("short info: extension")?.into(),
+    })
+}
+
+fn read_groups(value: &str) -> Vec<CompactString> {
+    read_list(value)
+}
+
+/// `maven-indexer`'s `lst2str`, which joins with `|`
+fn read_list<T: for<'a> From<&'a str>>(value: &str) -> Vec<T> {
+    if value.is_empty() {
+        return Vec::new();
+    }
+    value.split('|').map(|item| item.into()).collect()
+}
+
+fn read_size(value: &str) -> Result<Option<u64>, &'static str> {
+    if "-1" == value {
+        return Ok(None);
+    }
+
+    Ok(Some(value.parse::<u64>().map_err(|_| "reading size")?))
+}
+
+/// The two lists of groups an index may carry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GroupKind {
+    /// `rootGroups`: the first segment of each group, e.g. `org`
+    Root,
+    /// `allGroups`: every group
+    All,
+}
+
+impl GroupKind {
+    fn marker(self) -> &'static str {
+        match self {
+            GroupKind::Root => "rootGroups",
+            GroupKind::All => "allGroups",
+        }
+    }
+
+    fn list_field(self) -> &'static str {
+        match self {
+            GroupKind::Root => "rootGroupsList",
+            GroupKind::All => "allGroupsList",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AttachmentStatus {
+    Absent,
+    Present,
+    Unavailable,
+}
+
+impl AttachmentStatus {
+    fn read(value: &str) -> Result<AttachmentStatus, &'static str> {
+        Ok(match value.parse::<u8>() {
+            Ok(0) => AttachmentStatus::Absent,
+            Ok(1) => AttachmentStatus::Present,
+            Ok(2) => AttachmentStatus::Unavailable,
+            _ => return Err("invalid attachment value"),
+        })
+    }
+}
+
+bitflags! {
+    struct FieldFlag: u8 {
+        const INDEXED    = 0b0000_0001;
+        const TOKENIZED  = 0b0000_0010;
+        const STORED     = 0b0000_0100;
+        const COMPRESSED = 0b0000_1000;
+    }
+}
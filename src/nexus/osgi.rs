@@ -0,0 +1,105 @@
+/// The OSGi manifest headers `maven-indexer` copies into the index, as raw header values.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Osgi {
+    pub symbolic_name: Option<String>,
+    pub version: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub doc_url: Option<String>,
+    pub export_package: Option<String>,
+    pub export_service: Option<String>,
+    pub import_package: Option<String>,
+    pub require_bundle: Option<String>,
+    pub fragment_host: Option<String>,
+    pub provide_capability: Option<String>,
+    pub require_capability: Option<String>,
+    pub required_execution_environment: Option<String>,
+}
+
+impl Osgi {
+    /// The field for a header name, if it's one we know.
+    pub(super) fn header_mut(&mut self, header: &str) -> Option<&mut Option<String>> {
+        Some(match header {
+            "Bundle-SymbolicName" => &mut self.symbolic_name,
+            "Bundle-Version" => &mut self.version,
+            "Bundle-Name" => &mut self.name,
+            "Bundle-Description" => &mut self.description,
+            "Bundle-License" => &mut self.license,
+            "Bundle-DocURL" => &mut self.doc_url,
+            "Export-Package" => &mut self.export_package,
+            "Export-Service" => &mut self.export_service,
+            "Import-Package" => &mut self.import_package,
+            "Require-Bundle" => &mut self.require_bundle,
+            "Fragment-Host" => &mut self.fragment_host,
+            "Provide-Capability" => &mut self.provide_capability,
+            "Require-Capability" => &mut self.require_capability,
+            "Bundle-RequiredExecutionEnvironment" => &mut self.required_execution_environment,
+            _ => return None,
+        })
+    }
+
+    /// The headers which are present, in the order `maven-indexer` writes them.
+    pub fn headers(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("Bundle-SymbolicName", &self.symbolic_name),
+            ("Bundle-Version", &self.version),
+            ("Export-Package", &self.export_package),
+            ("Export-Service", &self.export_service),
+            ("Bundle-Description", &self.description),
+            ("Bundle-Name", &self.name),
+            ("Bundle-License", &self.license),
+            ("Bundle-DocURL", &self.doc_url),
+            ("Import-Package", &self.import_package),
+            ("Require-Bundle", &self.require_bundle),
+            ("Provide-Capability", &self.provide_capability),
+            ("Require-Capability", &self.require_capability),
+            ("Fragment-Host", &self.fragment_host),
+            (
+                "Bundle-RequiredExecutionEnvironment",
+                &self.required_execution_environment,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(header, value)| value.as_deref().map(|value| (header, value)))
+    }
+
+    /// The package names from `Export-Package`, without their attributes.
+    pub fn exported_packages(&self) -> Vec<&str> {
+        self.export_package
+            .as_deref()
+            .map(clause_names)
+            .unwrap_or_default()
+    }
+
+    /// The package names from `Import-Package`, without their attributes.
+    pub fn imported_packages(&self) -> Vec<&str> {
+        self.import_package
+            .as_deref()
+            .map(clause_names)
+            .unwrap_or_default()
+    }
+}
+
+/// `a.b;version="[1,2)",c.d` -> `["a.b", "c.d"]`; commas inside quotes don't split clauses.
+fn clause_names(header: &str) -> Vec<&str> {
+    let mut ret = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in header.char_indices().chain([(header.len(), ',')]) {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                let clause = &header[start..i];
+                let name = clause.split(';').next().unwrap_or_default().trim();
+                if !name.is_empty() {
+                    ret.push(name);
+                }
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    ret
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::vec;
+
+use anyhow::Result;
+
+use super::Event;
+use super::Frame;
+use super::IndexReader;
+use crate::Error;
+
+/// Records per batch handed to a worker; enough to make the channel overhead irrelevant.
+const BATCH: usize = 1_024;
+
+/// Batches per worker which may be framed, but not yet consumed.
+const IN_FLIGHT: usize = 4;
+
+/// A batch, and its position in the stream.
+type Numbered<T> = (u64, Vec<T>);
+
+/// As [`IndexReader`], but decoding records on a pool of threads.
+///
+/// Framing still happens on the calling thread, as does the consumption, and the events
+/// are produced in their original order.
+pub struct ParallelReader<R: BufRead> {
+    reader: IndexReader<R>,
+    work: mpsc::SyncSender<Numbered<Frame>>,
+    decoded: mpsc::Receiver<Numbered<Result<Event, Error>>>,
+    /// batches which have been decoded, but are waiting for an earlier one
+    ready: BTreeMap<u64, Vec<Result<Event, Error>>>,
+    current: vec::IntoIter<Result<Event, Error>>,
+    sent: u64,
+    received: u64,
+    max_in_flight: u64,
+    framing_done: bool,
+    /// the framing error which ended the stream, reported after the events before it
+    error: Option<Error>,
+    done: bool,
+}
+
+impl<R: BufRead> ParallelReader<R> {
+    /// Decode on `threads` worker threads; the calling thread frames records and restores their order.
+    pub fn new(reader: IndexReader<R>, threads: usize) -> ParallelReader<R> {
+        let threads = threads.max(1);
+        let (work, jobs) = mpsc::sync_channel(threads * IN_FLIGHT);
+        let (results, decoded) = mpsc::channel();
+
+        let jobs = Arc::new(Mutex::new(jobs));
+        for _ in 0..threads {
+            let jobs = Arc::clone(&jobs);
+            let results = results.clone();
+            // the threads exit when the reader (and hence `work`) is dropped
+            thread::spawn(move || decode_batches(&jobs, &results));
+        }
+
+        ParallelReader {
+            reader,
+            work,
+            decoded,
+            ready: BTreeMap::new(),
+            current: Vec::new().into_iter(),
+            sent: 0,
+            received: 0,
+            max_in_flight: (threads * IN_FLIGHT) as u64,
+            framing_done: false,
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Frame records and hand them to the workers, until there's enough work in flight.
+    fn dispatch(&mut self) {
+        while !self.framing_done && self.sent - self.received < self.max_in_flight {
+            let mut frames = Vec::with_capacity(BATCH);
+            while frames.len() < BATCH {
+                match self.reader.next_frame() {
+                    Ok(Some(frame)) => frames.push(frame),
+                    Ok(None) => {
+                        self.framing_done = true;
+                        break;
+                    }
+                    Err(e) => {
+                        self.error = Some(e);
+                        self.framing_done = true;
+                        break;
+                    }
+                }
+            }
+
+            if frames.is_empty() {
+                break;
+            }
+
+            self.work
+                .send((self.sent, frames))
+                .expect("decoding threads outlive the reader");
+            self.sent += 1;
+        }
+    }
+
+    /// The next batch, in order, waiting for it if necessary.
+    fn receive(&mut self) -> Vec<Result<Event, Error>> {
+        loop {
+            if let Some(batch) = self.ready.remove(&self.received) {
+                self.received += 1;
+                return batch;
+            }
+
+            let (number, batch) = self
+                .decoded
+                .recv()
+                .expect("decoding threads outlive the reader");
+            self.ready.insert(number, batch);
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ParallelReader<R> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Result<Event, Error>> {
+        loop {
+            if self.done {
+                return None;
+            }
+
+            if let Some(item) = self.current.next() {
+                self.done = item.is_err();
+                return Some(item);
+            }
+
+            self.dispatch();
+
+            if self.received == self.sent {
+                self.done = true;
+                return self.error.take().map(Err);
+            }
+
+            self.current = self.receive().into_iter();
+        }
+    }
+}
+
+fn decode_batches(
+    jobs: &Mutex<mpsc::Receiver<Numbered<Frame>>>,
+    results: &mpsc::Sender<Numbered<Result<Event, Error>>>,
+) {
+    loop {
+        // only hold the lock while waiting for work, not while doing it
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let (number, frames) = match job {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+
+        let events = frames.into_iter().map(Frame::decode).collect();
+        if results.send((number, events)).is_err() {
+            return;
+        }
+    }
+}
+
+/// As [`read`](super::read), but decoding on `threads` threads.
+pub fn read_parallel<R: BufRead, F>(from: R, threads: usize, mut cb: F) -> Result<()>
+where
+    F: FnMut(Event) -> Result<()>,
+{
+    for event in ParallelReader::new(IndexReader::new(from)?, threads) {
+        cb(event?)?;
+    }
+
+    Ok(())
+}
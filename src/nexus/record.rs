@@ -0,0 +1,280 @@
+use std::borrow::Cow;
+
+use compact_str::CompactString;
+
+use super::frame::Span;
+use super::read_checksum;
+use super::read_doc;
+use super::read_info;
+use super::to_event;
+use super::Checksum;
+use super::Doc;
+use super::Event;
+use super::FullInfo;
+use super::Name;
+use super::UniqId;
+use crate::java::decode_str;
+use crate::Error;
+use crate::Location;
+
+/// A record in the reader's buffer, split into fields, but not decoded;
+/// see [`IndexReader::next_record`](super::IndexReader::next_record).
+///
+/// Nothing is allocated unless a name or value is in Java's "modified" utf-8,
+/// or an owned type is asked for.
+#[derive(Copy, Clone, Debug)]
+pub struct RawRecord<'a> {
+    location: Location,
+    buf: &'a [u8],
+    fields: &'a [Span],
+}
+
+impl<'a> RawRecord<'a> {
+    pub(super) fn new(location: Location, buf: &'a [u8], fields: &'a [Span]) -> RawRecord<'a> {
+        RawRecord {
+            location,
+            buf,
+            fields,
+        }
+    }
+
+    /// Where the record starts.
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    /// The record, exactly as read.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// The number of fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The names and values, as they are in the file.
+    pub fn raw_fields(&self) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + 'a {
+        let buf = self.buf;
+        self.fields
+            .iter()
+            .map(move |(name, value)| (&buf[name.clone()], &buf[value.clone()]))
+    }
+
+    /// The names and values, decoded.
+    pub fn fields(&self) -> impl Iterator<Item = Result<(Cow<'a, str>, Cow<'a, str>), Error>> + 'a {
+        let record = *self;
+        (0..self.fields.len()).map(move |field| Ok((record.name(field)?, record.value(field)?)))
+    }
+
+    /// The value of the first field called `name`.
+    pub fn get(&self, name: &str) -> Result<Option<Cow<'a, str>>, Error> {
+        for field in 0..self.fields.len() {
+            if self.name(field)? == name {
+                return self.value(field).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The fields a doc is normally filtered on, or `None` if this isn't a doc.
+    pub fn doc(&self) -> Result<Option<DocRef<'a>>, Error> {
+        let mut you = None;
+        let mut eye = None;
+        let mut modified = None;
+        let mut name = None;
+        let mut description = None;
+        let mut checksum = None;
+
+        for field in 0..self.fields.len() {
+            let value = || self.value(field);
+            match self.name(field)?.as_ref() {
+                "del" => return Ok(None),
+                "u" => you = Some((field, value()?)),
+                "i" => eye = Some((field, value()?)),
+                "m" => modified = Some((field, value()?)),
+                "n" => name = Some(value()?),
+                "d" => description = Some(value()?),
+                // there are junk checksums out there, which aren't worth losing the doc over
+                "1" => checksum = read_checksum(&value()?),
+                _ => (),
+            }
+        }
+
+        let ((u, you), (i, eye), (m, modified)) = match (you, eye, modified) {
+            (Some(you), Some(eye), Some(modified)) => (you, eye, modified),
+            _ => return Ok(None),
+        };
+
+        let object_info = read_info(&eye).map_err(|reason| Error::MalformedInfo {
+            location: self.at(i),
+            value: eye.to_string(),
+            reason,
+        })?;
+
+        let modified = modified
+            .parse::<u64>()
+            .map_err(|_| Error::MalformedModified {
+                location: self.at(m),
+                value: modified.to_string(),
+            })?;
+
+        let id = match you {
+            Cow::Borrowed(you) => split_uniq(you),
+            Cow::Owned(you) => split_uniq(&you).map(UniqIdRef::into_owned),
+        }
+        .map_err(|reason| Error::MalformedUniq {
+            location: self.at(u),
+            value: self.value(u).map(|v| v.to_string()).unwrap_or_default(),
+            reason,
+        })?;
+
+        Ok(Some(DocRef {
+            id,
+            object_info,
+            modified,
+            name,
+            description,
+            checksum,
+            record: *self,
+        }))
+    }
+
+    /// As [`IndexReader`](super::IndexReader) would produce it.
+    pub fn to_event(&self) -> Result<Event, Error> {
+        Ok(to_event(self.decode_fields()?, self.location))
+    }
+
+    /// As `to_event`, but with `resync`, a record which can't be decoded is skipped.
+    pub(super) fn decode_event(&self, resync: bool) -> Result<Event, Error> {
+        match self.to_event() {
+            Ok(event) => Ok(event),
+            // the framing was fine, so skipping the record is all there is to do
+            Err(cause) if resync => Ok(Event::Error {
+                error: Error::Skipped {
+                    location: self.location,
+                    skipped: self.location.offset..self.location.offset + self.buf.len() as u64,
+                    cause: Box::new(cause),
+                },
+                raw: Vec::new(),
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(super) fn decode_fields(&self) -> Result<Vec<(Name, String)>, Error> {
+        let mut ret = Vec::with_capacity(self.fields.len());
+        for field in 0..self.fields.len() {
+            let name = match self.fields[field].0.len() {
+                1 => Name::from_byte(self.buf[self.fields[field].0.start]),
+                _ => Name::from_string(CompactString::from(self.name(field)?)),
+            };
+            ret.push((name, self.value(field)?.into_owned()));
+        }
+        Ok(ret)
+    }
+
+    fn name(&self, field: usize) -> Result<Cow<'a, str>, Error> {
+        let span = &self.fields[field].0;
+        match self.buf[span.clone()] {
+            // as with `Name::from_byte`
+            [byte] if !byte.is_ascii() => Ok(Cow::Owned(char::from(byte).to_string())),
+            _ => self.decode(field, span.clone()),
+        }
+    }
+
+    pub(super) fn value(&self, field: usize) -> Result<Cow<'a, str>, Error> {
+        self.decode(field, self.fields[field].1.clone())
+    }
+
+    fn decode(&self, field: usize, span: std::ops::Range<usize>) -> Result<Cow<'a, str>, Error> {
+        let offset = self.location.offset + span.start as u64;
+        decode_str(&self.buf[span], offset).map_err(|e| {
+            e.in_record(self.location.record.unwrap_or_default())
+                .in_field(field)
+        })
+    }
+
+    fn at(&self, field: usize) -> Location {
+        Location {
+            field: Some(field),
+            ..self.location
+        }
+    }
+}
+
+/// A [`Doc`], with only the fields which are normally filtered on.
+#[derive(Clone, Debug)]
+pub struct DocRef<'a> {
+    pub id: UniqIdRef<'a>,
+    /// owned, but the strings in here are short enough to be stored inline
+    pub object_info: FullInfo,
+    pub modified: u64,
+    pub name: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
+    pub checksum: Option<Checksum>,
+    record: RawRecord<'a>,
+}
+
+impl<'a> DocRef<'a> {
+    /// The record this doc came from, for any other fields.
+    pub fn record(&self) -> RawRecord<'a> {
+        self.record
+    }
+
+    /// The whole doc, as [`IndexReader`](super::IndexReader) would produce it.
+    pub fn to_doc(&self) -> Result<Doc, Error> {
+        read_doc(&self.record.decode_fields()?, self.record.location)
+    }
+}
+
+/// As [`UniqId`], but borrowed.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct UniqIdRef<'a> {
+    pub group: Cow<'a, str>,
+    pub artifact: Cow<'a, str>,
+    pub version: Cow<'a, str>,
+    pub classifier: Option<Cow<'a, str>>,
+    pub extension: Option<Cow<'a, str>>,
+}
+
+impl UniqIdRef<'_> {
+    pub fn to_uniq_id(&self) -> UniqId {
+        UniqId {
+            group: CompactString::from(self.group.as_ref()),
+            artifact: CompactString::from(self.artifact.as_ref()),
+            version: CompactString::from(self.version.as_ref()),
+            classifier: self.classifier.as_deref().map(CompactString::from),
+            extension: self.extension.as_deref().map(CompactString::from),
+        }
+    }
+
+    fn into_owned(self) -> UniqIdRef<'static> {
+        let owned = |value: Cow<str>| Cow::Owned(value.into_owned());
+        UniqIdRef {
+            group: owned(self.group),
+            artifact: owned(self.artifact),
+            version: owned(self.version),
+            classifier: self.classifier.map(owned),
+            extension: self.extension.map(owned),
+        }
+    }
+}
+
+/// `group|artifact|version|classifier[|extension]`, with `NA` for no classifier.
+pub(super) fn split_uniq(value: &str) -> Result<UniqIdRef<'_>, &'static str> {
+    let mut parts = value.split('|').map(Cow::Borrowed);
+
+    Ok(UniqIdRef {
+        group: parts.next().ok_or("short uniq: group")?,
+        artifact: parts.next().ok_or("short uniq: artifact")?,
+        version: parts.next().ok_or("short uniq: version")?,
+        classifier: Some(parts.next().ok_or("short uniq: classifier")?)
+            .filter(|classifier| "NA" != classifier),
+        extension: parts.next(),
+    })
+}
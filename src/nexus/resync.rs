@@ -0,0 +1,96 @@
+use super::FieldFlag;
+use super::Osgi;
+use super::ReaderOptions;
+
+/// The result of looking for the start of a record.
+pub(super) enum Scan {
+    /// A plausible record starts at this offset.
+    Found(usize),
+    /// Nothing before this offset; more data is needed to decide about the rest.
+    NeedMore(usize),
+    /// Nothing in the window.
+    NotFound,
+}
+
+/// Values larger than this aren't worth waiting for, just to check the following field.
+const LOOKAHEAD: usize = 4 * 1024;
+
+/// Find the first offset in `window` which looks like the start of a record; at `eof`,
+/// incomplete candidates are rejected instead of waiting for more.
+pub(super) fn scan(window: &[u8], options: &ReaderOptions, eof: bool) -> Scan {
+    for start in 0..window.len() {
+        match plausible(&window[start..], options) {
+            Some(true) => return Scan::Found(start),
+            Some(false) => (),
+            None if eof => (),
+            None => return Scan::NeedMore(start),
+        }
+    }
+    Scan::NotFound
+}
+
+/// A field count, then up to two field headers with names we recognise.
+/// `None` if `data` ends before it's possible to tell.
+fn plausible(data: &[u8], options: &ReaderOptions) -> Option<bool> {
+    let field_count = i32::from_be_bytes(data.get(..4)?.try_into().ok()?);
+    if field_count < 1 || field_count as usize > options.max_fields {
+        return Some(false);
+    }
+
+    let mut pos = 4;
+    for _ in 0..field_count.min(2) {
+        if FieldFlag::from_bits(*data.get(pos)?).is_none() {
+            return Some(false);
+        }
+        let name_len = usize::from(u16::from_be_bytes(
+            data.get(pos + 1..pos + 3)?.try_into().ok()?,
+        ));
+        if !(1..=LONGEST_NAME).contains(&name_len) {
+            return Some(false);
+        }
+        pos += 3;
+        if !known_name(data.get(pos..pos + name_len)?) {
+            return Some(false);
+        }
+        pos += name_len;
+        let value_len = i32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?);
+        if value_len < 0 || value_len as usize > options.max_value_len {
+            return Some(false);
+        }
+        pos += 4;
+        if value_len as usize > LOOKAHEAD {
+            break;
+        }
+        pos += value_len as usize;
+    }
+
+    Some(true)
+}
+
+/// `Bundle-RequiredExecutionEnvironment`
+const LONGEST_NAME: usize = 35;
+
+fn known_name(name: &[u8]) -> bool {
+    let name = match std::str::from_utf8(name) {
+        Ok(name) => name,
+        Err(_) => return false,
+    };
+    matches!(
+        name,
+        "u" | "i"
+            | "m"
+            | "n"
+            | "d"
+            | "1"
+            | "del"
+            | "DESCRIPTOR"
+            | "IDXINFO"
+            | "rootGroups"
+            | "rootGroupsList"
+            | "allGroups"
+            | "allGroupsList"
+            | "classNames"
+            | "px"
+            | "gx"
+    ) || Osgi::default().header_mut(name).is_some()
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+use serde::Serializer;
+
+use super::Name;
+use crate::Error;
+
+/// Checksums are lower-case hex, as in the index.
+pub(super) mod checksum {
+    use serde::de::Error as _;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+
+    use super::super::Checksum;
+
+    pub fn serialize<S: Serializer>(value: &Option<Checksum>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(checksum) => s.serialize_some(&hex::encode(checksum)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Checksum>, D::Error> {
+        let value = match Option::<String>::deserialize(d)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let mut checksum = [0u8; 20];
+        hex::decode_to_slice(&value, &mut checksum).map_err(D::Error::custom)?;
+        Ok(Some(checksum))
+    }
+}
+
+/// Milliseconds since the epoch become `{"millis": .., "rfc3339": ".."}`;
+/// either that or the bare number is accepted back.
+pub(super) mod timestamp {
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use crate::time::rfc3339;
+
+    #[derive(Serialize)]
+    struct Both {
+        millis: u64,
+        rfc3339: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Either {
+        Millis(u64),
+        Both { millis: u64 },
+    }
+
+    pub fn serialize<S: Serializer>(millis: &u64, s: S) -> Result<S::Ok, S::Error> {
+        Both {
+            millis: *millis,
+            rfc3339: rfc3339(*millis),
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+        Ok(match Either::deserialize(d)? {
+            Either::Millis(millis) | Either::Both { millis } => millis,
+        })
+    }
+}
+
+/// Errors are just their message; they can't be read back.
+pub(super) fn error_message<S: Serializer>(error: &Error, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(error)
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.as_str())
+    }
+}
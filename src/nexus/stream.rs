@@ -0,0 +1,87 @@
+use futures_core::Stream;
+use tokio::io::AsyncBufRead;
+
+use super::frame::Framer;
+use super::read_version;
+use super::Event;
+use super::Frame;
+use super::ReaderOptions;
+use crate::java::AsyncDataInput;
+use crate::Error;
+
+/// As [`IndexReader`](super::IndexReader), but for tokio's [`AsyncBufRead`].
+///
+/// The framing is the same code, run over what has been read so far, and carried
+/// on with more when that runs out.
+pub struct AsyncIndexReader<R: AsyncBufRead + Unpin> {
+    from: AsyncDataInput<R>,
+    framer: Framer,
+    version: i8,
+    timestamp_ms: i64,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncIndexReader<R> {
+    /// Reads the header immediately.
+    pub async fn new(from: R) -> Result<AsyncIndexReader<R>, Error> {
+        AsyncIndexReader::with_options(from, ReaderOptions::default()).await
+    }
+
+    pub async fn with_options(
+        from: R,
+        options: ReaderOptions,
+    ) -> Result<AsyncIndexReader<R>, Error> {
+        let mut from = AsyncDataInput::new(from);
+
+        let version = read_version(from.read_byte().await?)?;
+        let timestamp_ms = from.read_long().await?;
+
+        Ok(AsyncIndexReader {
+            from,
+            framer: Framer::new(options),
+            version,
+            timestamp_ms,
+        })
+    }
+
+    pub fn version(&self) -> i8 {
+        self.version
+    }
+
+    pub fn timestamp_ms(&self) -> i64 {
+        self.timestamp_ms
+    }
+
+    pub fn records(&self) -> u64 {
+        self.framer.records
+    }
+
+    /// As [`IndexReader::next_frame`](super::IndexReader::next_frame).
+    pub async fn next_frame(&mut self) -> Result<Option<Frame>, Error> {
+        let framer = &mut self.framer;
+        self.from.retry(|from| framer.next_frame(from)).await
+    }
+
+    /// The next event, or `None` at the end of the stream.
+    pub async fn next_event(&mut self) -> Result<Option<Event>, Error> {
+        self.next_frame().await?.map(Frame::decode).transpose()
+    }
+
+    /// As with the `Iterator`, an `Err` is the last item produced.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Event, Error>> {
+        futures_util::stream::unfold(Some(self), |reader| async move {
+            let mut reader = reader?;
+            match reader.next_event().await {
+                Ok(Some(event)) => Some((Ok(event), Some(reader))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+}
+
+/// Read the header, then stream the events.
+pub async fn read_stream<R: AsyncBufRead + Unpin>(
+    from: R,
+) -> Result<impl Stream<Item = Result<Event, Error>>, Error> {
+    Ok(AsyncIndexReader::new(from).await?.into_stream())
+}
@@ -0,0 +1,157 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use super::AttachmentStatus;
+use super::Doc;
+use super::Event;
+use super::FieldFlag;
+use super::FullInfo;
+use super::GroupKind;
+use super::Name;
+use super::UniqId;
+use crate::java::DataOutput;
+
+/// Writes the format [`read`](super::read) reads, such that reading it back produces the same events.
+pub struct Writer<W: Write> {
+    to: DataOutput<W>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Writes the header immediately; `timestamp_ms` is `-1` for "unknown", as in Java.
+    pub fn new(to: W, timestamp_ms: i64) -> Result<Writer<W>> {
+        let mut to = DataOutput::new(to);
+        to.write_byte(1)?;
+        to.write_long(timestamp_ms)?;
+        Ok(Writer { to })
+    }
+
+    pub fn write(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::Doc(doc) => self.write_doc(doc),
+            Event::Delete(id) => self.write_delete(id),
+            Event::Descriptor {
+                version,
+                repository_id,
+            } => self.write_descriptor(version, repository_id),
+            Event::Groups { kind, groups } => self.write_groups(*kind, groups),
+            Event::Error { raw, .. } => self.write_fields(raw),
+        }
+    }
+
+    pub fn write_doc(&mut self, doc: &Doc) -> Result<()> {
+        let mut fields = Vec::with_capacity(6);
+        fields.push((Name::U, format!("{:#}", doc.id)));
+        fields.push((Name::M, doc.modified.to_string()));
+        fields.push((Name::I, write_info(&doc.object_info)));
+        if let Some(name) = &doc.name {
+            fields.push((Name::N, name.to_string()));
+        }
+        if let Some(description) = &doc.description {
+            fields.push((Name::D, description.to_string()));
+        }
+        if let Some(checksum) = &doc.checksum {
+            fields.push((Name::Checksum, hex::encode(checksum)));
+        }
+        if let Some(class_names) = &doc.class_names {
+            fields.push((Name::ClassNames, class_names.join("\n")));
+        }
+        if let Some(plugin) = &doc.plugin {
+            if let Some(prefix) = &plugin.prefix {
+                fields.push((Name::PluginPrefix, prefix.to_string()));
+            }
+            fields.push((Name::PluginGoals, plugin.goals.join("|")));
+        }
+        if let Some(osgi) = &doc.osgi {
+            for (header, value) in osgi.headers() {
+                fields.push((Name::Other(header.into()), value.to_string()));
+            }
+        }
+        for (name, value) in &doc.extra {
+            fields.push((Name::Other(name.clone()), value.to_string()));
+        }
+        self.write_fields(&fields)
+    }
+
+    pub fn write_delete(&mut self, id: &UniqId) -> Result<()> {
+        self.write_fields(&[(Name::Other("del".into()), format!("{id:#}"))])
+    }
+
+    /// The record describing the index itself, e.g. `("1.0", "central")`.
+    pub fn write_descriptor(&mut self, version: &str, repository_id: &str) -> Result<()> {
+        self.write_fields(&[
+            (Name::Other("DESCRIPTOR".into()), "NexusIndex".to_string()),
+            (
+                Name::Other("IDXINFO".into()),
+                format!("{version}|{repository_id}"),
+            ),
+        ])
+    }
+
+    pub fn write_groups<S: AsRef<str>>(&mut self, kind: GroupKind, groups: &[S]) -> Result<()> {
+        let groups = groups
+            .iter()
+            .map(|group| group.as_ref())
+            .collect::<Vec<_>>()
+            .join("|");
+        self.write_fields(&[
+            (Name::Other(kind.marker().into()), kind.marker().to_string()),
+            (Name::Other(kind.list_field().into()), groups),
+        ])
+    }
+
+    /// Write an arbitrary record, with the flags `maven-indexer` would use for each field.
+    pub fn write_fields(&mut self, fields: &[(Name, String)]) -> Result<()> {
+        self.to.write_int(i32::try_from(fields.len())?)?;
+        for (name, value) in fields {
+            self.to.write_byte(field_flags(name).bits() as i8)?;
+            self.to.write_utf(name.as_str())?;
+            self.to.write_string(value)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> Result<W> {
+        self.to.flush()?;
+        Ok(self.to.into_inner())
+    }
+}
+
+fn field_flags(name: &Name) -> FieldFlag {
+    match name {
+        Name::N | Name::D | Name::ClassNames => {
+            FieldFlag::INDEXED | FieldFlag::TOKENIZED | FieldFlag::STORED
+        }
+        Name::U | Name::Checksum => FieldFlag::INDEXED | FieldFlag::STORED,
+        Name::I | Name::M | Name::PluginPrefix | Name::PluginGoals => FieldFlag::STORED,
+        Name::Other(other) => match other.as_str() {
+            "del" | "DESCRIPTOR" | "rootGroups" | "allGroups" => {
+                FieldFlag::INDEXED | FieldFlag::STORED
+            }
+            _ => FieldFlag::STORED,
+        },
+    }
+}
+
+fn write_info(info: &FullInfo) -> String {
+    format!(
+        "{}|{}|{}|{}|{}|{}|{}",
+        info.packaging,
+        info.last_modified,
+        info.size
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-1".to_string()),
+        write_attachment(info.source_attached),
+        write_attachment(info.javadoc_attached),
+        write_attachment(info.signature_attached),
+        info.extension,
+    )
+}
+
+fn write_attachment(status: AttachmentStatus) -> char {
+    match status {
+        AttachmentStatus::Absent => '0',
+        AttachmentStatus::Present => '1',
+        AttachmentStatus::Unavailable => '2',
+    }
+}
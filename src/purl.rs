@@ -0,0 +1,127 @@
+//! [Package URLs](https://github.com/package-url/purl-spec), as used by SBOM and
+//! vulnerability tooling, e.g. `pkg:maven/org.example/example@1.0?classifier=sources&type=zip`.
+
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use compact_str::CompactString;
+
+use crate::nexus::UniqId;
+
+/// The purl for an artifact.
+///
+/// Qualifiers are in order, as the spec requires. `type` is left out when it's `jar`,
+/// which is the default, so e.g. a `jar` is just `pkg:maven/org.example/example@1.0`.
+pub fn to_purl(id: &UniqId) -> String {
+    let mut ret = format!(
+        "pkg:maven/{}/{}@{}",
+        encode(&id.group),
+        encode(&id.artifact),
+        encode(&id.version)
+    );
+
+    let mut qualifiers = Vec::with_capacity(2);
+    if let Some(classifier) = &id.classifier {
+        qualifiers.push(("classifier", classifier));
+    }
+    if let Some(extension) = id.extension.as_ref().filter(|e| "jar" != e.as_str()) {
+        qualifiers.push(("type", extension));
+    }
+    for (i, (key, value)) in qualifiers.into_iter().enumerate() {
+        ret.push(if 0 == i { '?' } else { '&' });
+        ret.push_str(key);
+        ret.push('=');
+        ret.push_str(&encode(value));
+    }
+    ret
+}
+
+/// The artifact a `pkg:maven` purl is for.
+///
+/// Qualifiers other than `classifier` and `type` (e.g. `repository_url`) and any
+/// subpath are ignored. Without a `type`, there's no extension.
+pub fn parse(purl: &str) -> Result<UniqId> {
+    let rest = match purl.get(..4) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("pkg:") => &purl[4..],
+        _ => bail!("not a purl: {purl:?}"),
+    };
+    // `pkg://` is tolerated by the spec
+    let rest = rest.trim_start_matches('/');
+    let rest = rest.split_once('#').map_or(rest, |(rest, _subpath)| rest);
+    let (rest, qualifiers) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let rest = match rest.split_once('/') {
+        Some((kind, rest)) if kind.eq_ignore_ascii_case("maven") => rest,
+        _ => bail!("not a maven purl: {purl:?}"),
+    };
+    let (path, version) = match rest.rsplit_once('@') {
+        Some(split) => split,
+        None => bail!("no version: {purl:?}"),
+    };
+    let (group, artifact) = match path.trim_end_matches('/').rsplit_once('/') {
+        Some((group, artifact)) if !group.contains('/') => (group, artifact),
+        _ => bail!("expected maven/group/artifact: {purl:?}"),
+    };
+
+    let mut id = UniqId {
+        group: decode(group)?,
+        artifact: decode(artifact)?,
+        version: decode(version)?,
+        classifier: None,
+        extension: None,
+    };
+    ensure!(
+        !id.group.is_empty() && !id.artifact.is_empty() && !id.version.is_empty(),
+        "empty coordinate: {purl:?}"
+    );
+
+    for qualifier in qualifiers.split('&').filter(|q| !q.is_empty()) {
+        let (key, value) = match qualifier.split_once('=') {
+            Some(split) => split,
+            None => bail!("qualifier without a value: {qualifier:?} in {purl:?}"),
+        };
+        // "a key=value pair with an empty value is the same as no key/value at all"
+        let value = Some(decode(value)?).filter(|value| !value.is_empty());
+        match key.to_ascii_lowercase().as_str() {
+            "classifier" => id.classifier = value,
+            "type" => id.extension = value,
+            _ => (),
+        }
+    }
+
+    Ok(id)
+}
+
+/// Everything but the unreserved characters is percent-encoded.
+fn encode(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            ret.push(char::from(b));
+        } else {
+            ret.push_str(&format!("%{b:02X}"));
+        }
+    }
+    ret
+}
+
+fn decode(value: &str) -> Result<CompactString> {
+    let mut ret = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(b) = bytes.next() {
+        if b'%' != b {
+            ret.push(b);
+            continue;
+        }
+        let mut digit = || bytes.next().and_then(|b| char::from(b).to_digit(16));
+        match (digit(), digit()) {
+            (Some(high), Some(low)) => ret.push((high * 16 + low) as u8),
+            _ => bail!("bad percent-encoding: {value:?}"),
+        }
+    }
+    Ok(CompactString::from(
+        String::from_utf8(ret).with_context(|| anyhow!("decoding {value:?}"))?,
+    ))
+}
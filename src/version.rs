@@ -0,0 +1,513 @@
+//! Maven's idea of versions, and of how they're ordered.
+
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::fmt;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+use std::str::FromStr;
+
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Result;
+
+use crate::nexus::Doc;
+
+/// A version, ordered as Maven's `ComparableVersion` orders them.
+///
+/// Anything is a version; strange strings are just ordered strangely. Versions which differ
+/// only in things Maven considers insignificant, like `1.0` and `1`, or `1-ga` and `1`,
+/// are equal.
+///
+/// Qualifiers are ordered `alpha` < `beta` < `milestone` < `rc` < `snapshot` < (release) < `sp`,
+/// then anything unrecognised, alphabetically. `a`, `b` and `m` directly followed by a number
+/// are `alpha`, `beta` and `milestone`; `cr` is `rc`, and `ga`, `final` and `release`
+/// are nothing at all.
+#[derive(Clone, Debug)]
+pub struct MavenVersion {
+    original: String,
+    items: Vec<Item>,
+}
+
+/// Broadly, what a version is for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VersionKind {
+    Release,
+    /// an `alpha`, `beta`, `milestone` or `rc`
+    PreRelease,
+    /// `-SNAPSHOT`, or a timestamped snapshot, like `1.0-20240101.123456-1`
+    Snapshot,
+}
+
+/// A set of versions, in Maven's range syntax, e.g. `[1.0,2.0)`, `(,1.5]`, `[1.2]`,
+/// or a union of these, like `(,1.0],[1.2,)`.
+///
+/// A bare version, e.g. `1.0`, is Maven's "soft" requirement: any version will do,
+/// but that one is [`recommended`](Self::recommended).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRange {
+    recommended: Option<MavenVersion>,
+    /// in order, and not overlapping
+    restrictions: Vec<(Bound<MavenVersion>, Bound<MavenVersion>)>,
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    /// digits, without leading zeros, except for zero itself
+    Int(String),
+    /// lower case, with the aliases applied
+    Str(String),
+    List(Vec<Item>),
+}
+
+const QUALIFIERS: [&str; 7] = ["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+const SNAPSHOT: usize = 4;
+
+impl MavenVersion {
+    pub fn parse(version: &str) -> MavenVersion {
+        MavenVersion {
+            original: version.to_string(),
+            items: parse_items(&version.to_lowercase()),
+        }
+    }
+
+    /// The version, as it was given.
+    pub fn as_str(&self) -> &str {
+        &self.original
+    }
+
+    /// The version, as Maven understands it, e.g. `1-rc-1` for `1.0-CR-1`.
+    pub fn canonical(&self) -> String {
+        let mut ret = String::new();
+        write_list(&mut ret, &self.items);
+        ret
+    }
+
+    pub fn kind(&self) -> VersionKind {
+        if self.is_snapshot() {
+            VersionKind::Snapshot
+        } else if any_qualifier(&self.items, &|index| index < SNAPSHOT) {
+            VersionKind::PreRelease
+        } else {
+            VersionKind::Release
+        }
+    }
+
+    pub fn is_release(&self) -> bool {
+        VersionKind::Release == self.kind()
+    }
+
+    pub fn is_pre_release(&self) -> bool {
+        VersionKind::PreRelease == self.kind()
+    }
+
+    /// As Maven's `ArtifactUtils.isSnapshot`.
+    pub fn is_snapshot(&self) -> bool {
+        self.original.ends_with("SNAPSHOT") || is_timestamped_snapshot(&self.original)
+    }
+}
+
+impl FromStr for MavenVersion {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(MavenVersion::parse(s))
+    }
+}
+
+impl fmt::Display for MavenVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.original)
+    }
+}
+
+impl Ord for MavenVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_lists(&self.items, &other.items)
+    }
+}
+
+impl PartialOrd for MavenVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MavenVersion {
+    fn eq(&self, other: &Self) -> bool {
+        Ordering::Equal == self.cmp(other)
+    }
+}
+
+impl Eq for MavenVersion {}
+
+impl VersionRange {
+    /// As Maven's `VersionRange.createFromVersionSpec`.
+    pub fn parse(spec: &str) -> Result<VersionRange> {
+        let mut restrictions = Vec::new();
+        let mut rest = spec.trim();
+
+        while rest.starts_with(['[', '(']) {
+            let end = match rest.find([']', ')']) {
+                Some(end) => end,
+                None => bail!("unbounded range: {spec:?}"),
+            };
+            let restriction = parse_restriction(&rest[..=end])?;
+            if let Some(previous) = restrictions.last() {
+                let ordered = match (upper_version(previous), lower_version(&restriction)) {
+                    (Some(upper), Some(lower)) => lower >= upper,
+                    _ => false,
+                };
+                ensure!(ordered, "ranges overlap, or are out of order: {spec:?}");
+            }
+            restrictions.push(restriction);
+
+            rest = rest[end + 1..].trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after.trim_start();
+            }
+        }
+
+        if rest.is_empty() {
+            ensure!(!restrictions.is_empty(), "empty range");
+            return Ok(VersionRange {
+                recommended: None,
+                restrictions,
+            });
+        }
+
+        ensure!(
+            restrictions.is_empty(),
+            "only ranges are allowed in a set of ranges: {spec:?}"
+        );
+        Ok(VersionRange {
+            recommended: Some(MavenVersion::parse(rest)),
+            restrictions: vec![(Bound::Unbounded, Bound::Unbounded)],
+        })
+    }
+
+    /// The version from a bare version spec, like `1.0`.
+    pub fn recommended(&self) -> Option<&MavenVersion> {
+        self.recommended.as_ref()
+    }
+
+    pub fn contains(&self, version: &MavenVersion) -> bool {
+        self.restrictions
+            .iter()
+            .any(|restriction| restriction.contains(version))
+    }
+
+    /// As [`contains`](Self::contains), parsing `version` first.
+    pub fn matches(&self, version: &str) -> bool {
+        self.contains(&MavenVersion::parse(version))
+    }
+
+    /// The versions in the range, e.g. from [`db::find_versions`](crate::db::find_versions).
+    pub fn filter<'s, T: AsRef<str>>(
+        &'s self,
+        versions: impl IntoIterator<Item = T> + 's,
+    ) -> impl Iterator<Item = T> + 's {
+        versions
+            .into_iter()
+            .filter(move |version| self.matches(version.as_ref()))
+    }
+
+    /// The docs whose version is in the range.
+    pub fn filter_docs<'s, 'd: 's>(
+        &'s self,
+        docs: impl IntoIterator<Item = &'d Doc> + 's,
+    ) -> impl Iterator<Item = &'d Doc> + 's {
+        docs.into_iter()
+            .filter(move |doc| self.matches(&doc.id.version))
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VersionRange::parse(s)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(recommended) = &self.recommended {
+            return write!(f, "{recommended}");
+        }
+
+        for (i, (lower, upper)) in self.restrictions.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            match (lower, upper) {
+                (Bound::Included(lower), Bound::Included(upper)) if lower == upper => {
+                    write!(f, "[{lower}]")?;
+                    continue;
+                }
+                (Bound::Included(lower), _) => write!(f, "[{lower},")?,
+                (Bound::Excluded(lower), _) => write!(f, "({lower},")?,
+                (Bound::Unbounded, _) => f.write_str("(,")?,
+            }
+            match upper {
+                Bound::Included(upper) => write!(f, "{upper}]")?,
+                Bound::Excluded(upper) => write!(f, "{upper})")?,
+                Bound::Unbounded => f.write_str(")")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `[1.0,2.0)`, `(,1.0]`, `[1.0]`, etc.; an empty side is unbounded.
+fn parse_restriction(spec: &str) -> Result<(Bound<MavenVersion>, Bound<MavenVersion>)> {
+    let lower_inclusive = spec.starts_with('[');
+    let upper_inclusive = spec.ends_with(']');
+    let inner = spec[1..spec.len() - 1].trim();
+
+    let (lower, upper) = match inner.split_once(',') {
+        Some((lower, upper)) => (lower.trim(), upper.trim()),
+        None => {
+            ensure!(
+                lower_inclusive && upper_inclusive && !inner.is_empty(),
+                "a single version must be surrounded by []: {spec:?}"
+            );
+            let version = MavenVersion::parse(inner);
+            return Ok((Bound::Included(version.clone()), Bound::Included(version)));
+        }
+    };
+    ensure!(!upper.contains(','), "too many commas in range: {spec:?}");
+
+    let bound = |version: &str, inclusive: bool| match (version, inclusive) {
+        ("", _) => Bound::Unbounded,
+        (version, true) => Bound::Included(MavenVersion::parse(version)),
+        (version, false) => Bound::Excluded(MavenVersion::parse(version)),
+    };
+    let restriction = (bound(lower, lower_inclusive), bound(upper, upper_inclusive));
+
+    if let (Some(lower), Some(upper)) = (lower_version(&restriction), upper_version(&restriction)) {
+        ensure!(lower <= upper, "range defies version ordering: {spec:?}");
+    }
+    Ok(restriction)
+}
+
+fn lower_version(
+    restriction: &(Bound<MavenVersion>, Bound<MavenVersion>),
+) -> Option<&MavenVersion> {
+    match &restriction.0 {
+        Bound::Included(version) | Bound::Excluded(version) => Some(version),
+        Bound::Unbounded => None,
+    }
+}
+
+fn upper_version(
+    restriction: &(Bound<MavenVersion>, Bound<MavenVersion>),
+) -> Option<&MavenVersion> {
+    match &restriction.1 {
+        Bound::Included(version) | Bound::Excluded(version) => Some(version),
+        Bound::Unbounded => None,
+    }
+}
+
+/// `ComparableVersion.parseVersion`: `.` separates items, and `-`, or a change between
+/// digits and letters, starts a new sub-list.
+fn parse_items(version: &str) -> Vec<Item> {
+    // the lists being built; the innermost is last
+    let mut stack = vec![Vec::new()];
+    let mut is_digit = false;
+    let mut start = 0;
+
+    for (i, c) in version.char_indices() {
+        let list = stack.last_mut().expect("never empty");
+        match c {
+            '.' => {
+                list.push(item_or_zero(&version[start..i], is_digit));
+                start = i + 1;
+            }
+            '-' => {
+                list.push(item_or_zero(&version[start..i], is_digit));
+                start = i + 1;
+                stack.push(Vec::new());
+            }
+            _ if c.is_ascii_digit() => {
+                if !is_digit && i > start {
+                    list.push(Item::string(&version[start..i], true));
+                    start = i;
+                    stack.push(Vec::new());
+                }
+                is_digit = true;
+            }
+            _ => {
+                if is_digit && i > start {
+                    list.push(Item::int(&version[start..i]));
+                    start = i;
+                    stack.push(Vec::new());
+                }
+                is_digit = false;
+            }
+        }
+    }
+
+    if version.len() > start {
+        let rest = &version[start..];
+        let list = stack.last_mut().expect("never empty");
+        list.push(if is_digit {
+            Item::int(rest)
+        } else {
+            Item::string(rest, false)
+        });
+    }
+
+    // each list is an item in the one before it
+    let mut items = stack.pop().expect("never empty");
+    normalize(&mut items);
+    while let Some(mut parent) = stack.pop() {
+        parent.push(Item::List(items));
+        normalize(&mut parent);
+        items = parent;
+    }
+    items
+}
+
+fn item_or_zero(item: &str, is_digit: bool) -> Item {
+    if item.is_empty() {
+        Item::Int("0".to_string())
+    } else if is_digit {
+        Item::int(item)
+    } else {
+        Item::string(item, false)
+    }
+}
+
+/// Drop trailing nulls (`0`, `""`, `[]`), but not past a non-list item.
+fn normalize(items: &mut Vec<Item>) {
+    let mut i = items.len();
+    while i > 0 {
+        i -= 1;
+        if items[i].is_null() {
+            items.remove(i);
+        } else if !matches!(items[i], Item::List(_)) {
+            break;
+        }
+    }
+}
+
+impl Item {
+    fn int(digits: &str) -> Item {
+        let digits = digits.trim_start_matches('0');
+        Item::Int(if digits.is_empty() { "0" } else { digits }.to_string())
+    }
+
+    fn string(value: &str, followed_by_digit: bool) -> Item {
+        let value = match value {
+            "a" if followed_by_digit => "alpha",
+            "b" if followed_by_digit => "beta",
+            "m" if followed_by_digit => "milestone",
+            "ga" | "final" | "release" => "",
+            "cr" => "rc",
+            other => other,
+        };
+        Item::Str(value.to_string())
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            Item::Int(value) => "0" == value,
+            Item::Str(value) => value.is_empty(),
+            Item::List(items) => items.is_empty(),
+        }
+    }
+
+    /// `None` is the absence of an item, e.g. the fourth item of `1.0.0`.
+    fn compare(&self, other: Option<&Item>) -> Ordering {
+        match (self, other) {
+            (Item::Int(value), None) => {
+                if "0" == value {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (Item::Int(left), Some(Item::Int(right))) => {
+                (left.len(), left).cmp(&(right.len(), right))
+            }
+            (Item::Int(_), Some(_)) => Ordering::Greater,
+
+            (Item::Str(value), None) => compare_qualifiers(value, ""),
+            (Item::Str(_), Some(Item::Int(_))) => Ordering::Less,
+            (Item::Str(left), Some(Item::Str(right))) => compare_qualifiers(left, right),
+            (Item::Str(_), Some(Item::List(_))) => Ordering::Less,
+
+            (Item::List(items), None) => match items.first() {
+                Some(first) => first.compare(None),
+                None => Ordering::Equal,
+            },
+            (Item::List(_), Some(Item::Int(_))) => Ordering::Less,
+            (Item::List(_), Some(Item::Str(_))) => Ordering::Greater,
+            (Item::List(left), Some(Item::List(right))) => compare_lists(left, right),
+        }
+    }
+}
+
+fn compare_lists(left: &[Item], right: &[Item]) -> Ordering {
+    for i in 0..left.len().max(right.len()) {
+        let result = match (left.get(i), right.get(i)) {
+            (Some(l), r) => l.compare(r),
+            (None, Some(r)) => r.compare(None).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if Ordering::Equal != result {
+            return result;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_qualifiers(left: &str, right: &str) -> Ordering {
+    qualifier_key(left).cmp(&qualifier_key(right))
+}
+
+/// Known qualifiers by their position, then the rest alphabetically.
+fn qualifier_key(value: &str) -> (usize, &str) {
+    match QUALIFIERS.iter().position(|q| *q == value) {
+        Some(index) => (index, ""),
+        None => (QUALIFIERS.len(), value),
+    }
+}
+
+fn any_qualifier(items: &[Item], pred: &dyn Fn(usize) -> bool) -> bool {
+    items.iter().any(|item| match item {
+        Item::Int(_) => false,
+        Item::Str(value) => QUALIFIERS.iter().position(|q| q == value).is_some_and(pred),
+        Item::List(items) => any_qualifier(items, pred),
+    })
+}
+
+fn write_list(out: &mut String, items: &[Item]) {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(if matches!(item, Item::List(_)) {
+                '-'
+            } else {
+                '.'
+            });
+        }
+        match item {
+            Item::Int(value) | Item::Str(value) => out.push_str(value),
+            Item::List(items) => write_list(out, items),
+        }
+    }
+}
+
+/// `1.0-20240101.123456-1`
+pub(crate) fn is_timestamped_snapshot(version: &str) -> bool {
+    let mut parts = version.rsplitn(3, '-');
+    let (build, timestamp) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(build), Some(timestamp), Some(_)) => (build, timestamp),
+        _ => return false,
+    };
+    let digits = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit());
+    !build.is_empty()
+        && build.bytes().all(|b| b.is_ascii_digit())
+        && matches!(timestamp.split_once('.'), Some((date, time)) if digits(date, 8) && digits(time, 6))
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use tokio::io::AsyncBufRead;
+use tokio::io::BufReader;
+
+use nexers::java::AsyncDataInput;
+use nexers::java::DataInput;
+use nexers::nexus::AsyncIndexReader;
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::ReaderOptions;
+use nexers::nexus::Writer;
+use nexers::Error;
+
+use common::docs;
+use common::only_docs;
+use common::TINY;
+
+#[tokio::test]
+async fn matches_sync() -> Result<()> {
+    let sync = IndexReader::new(TINY)?.collect::<Result<Vec<_>, _>>()?;
+
+    let reader = AsyncIndexReader::new(TINY).await?;
+    assert_eq!(1, reader.version());
+    assert_eq!(1_545_614_688_784, reader.timestamp_ms());
+    let events = reader.into_stream().try_collect::<Vec<_>>().await?;
+
+    assert_eq!(only_docs(sync), only_docs(events));
+    Ok(())
+}
+
+#[tokio::test]
+async fn truncated() -> Result<()> {
+    let events = nexers::nexus::read_stream(&TINY[..TINY.len() - 3])
+        .await?
+        .try_collect::<Vec<_>>()
+        .await;
+    assert!(events.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn resync_matches_sync() -> Result<()> {
+    let mut data = TINY.to_vec();
+    // the second record's field count
+    data[0xcf..0xd3].copy_from_slice(&[0xff; 4]);
+    let options = ReaderOptions {
+        resync: true,
+        ..ReaderOptions::default()
+    };
+
+    let sync = IndexReader::with_options(data.as_slice(), options.clone())?
+        .map(|event| event.map(|event| format!("{event:?}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(sync[1].contains("Skipped"));
+
+    assert_eq!(sync, summarise(data.as_slice(), options.clone()).await?);
+    // so every record, and the resync, runs out part way through
+    let trickle = BufReader::with_capacity(1, data.as_slice());
+    assert_eq!(sync, summarise(trickle, options).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn trickle_matches_sync() -> Result<()> {
+    let mut data = TINY.to_vec();
+    // garbage between the records
+    data.splice(0xcf..0xcf, [0xff; 100]);
+    let options = ReaderOptions {
+        resync: true,
+        ..ReaderOptions::default()
+    };
+
+    let sync = IndexReader::with_options(data.as_slice(), options.clone())?
+        .map(|event| event.map(|event| format!("{event:?}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(3, sync.len());
+
+    for capacity in [1, 7, 64] {
+        let trickle = BufReader::with_capacity(capacity, data.as_slice());
+        assert_eq!(sync, summarise(trickle, options.clone()).await?);
+    }
+
+    let trickle = BufReader::with_capacity(1, &TINY[..TINY.len() - 3]);
+    let events = nexers::nexus::read_stream(trickle)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await;
+    assert!(matches!(events, Err(Error::Truncated { .. })));
+    Ok(())
+}
+
+#[tokio::test]
+async fn data_input_matches_sync() -> Result<()> {
+    let mut sync = DataInput::new(TINY);
+    let mut trickle = AsyncDataInput::new(BufReader::with_capacity(1, TINY));
+    assert_eq!(sync.read_byte()?, trickle.read_byte().await?);
+    assert_eq!(sync.read_long()?, trickle.read_long().await?);
+    // the first record: its field count, then the first field's flags, name and value
+    assert_eq!(sync.read_int()?, trickle.read_int().await?);
+    assert_eq!(sync.read_byte()?, trickle.read_byte().await?);
+    let name_len = sync.read_unsigned_short()?;
+    assert_eq!(name_len, trickle.read_unsigned_short().await?);
+    let name_len = usize::from(name_len);
+    assert_eq!(
+        sync.read_utf8(name_len)?,
+        trickle.read_utf8(name_len).await?
+    );
+    assert_eq!(sync.position(), trickle.position());
+    assert!(!trickle.check_eof().await?);
+
+    let mut short = AsyncDataInput::new(&TINY[..4]);
+    assert!(matches!(
+        short.read_long().await,
+        Err(Error::Truncated { .. })
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn large_record() -> Result<()> {
+    let mut doc = docs().remove(0);
+    doc.name = Some("x".repeat(4 * 1024 * 1024));
+    let mut index = Writer::new(Vec::new(), 0)?;
+    index.write(&Event::Doc(doc.clone()))?;
+    let index = index.into_inner()?;
+
+    // the record arrives in thousands of pieces, nearly all of them in the one long value
+    let trickle = BufReader::with_capacity(1024, index.as_slice());
+    let events = AsyncIndexReader::new(trickle)
+        .await?
+        .into_stream()
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(vec![doc], only_docs(events));
+    Ok(())
+}
+
+async fn summarise<R: AsyncBufRead + Unpin>(
+    from: R,
+    options: ReaderOptions,
+) -> Result<Vec<String>> {
+    Ok(AsyncIndexReader::with_options(from, options)
+        .await?
+        .into_stream()
+        .map_ok(|event| format!("{event:?}"))
+        .try_collect::<Vec<_>>()
+        .await?)
+}
@@ -0,0 +1,34 @@
+//! Fixtures for the tests; each test crate uses some of them.
+#![allow(dead_code)]
+
+use nexers::nexus::Doc;
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::UniqId;
+
+/// Two docs, `yom:yom:1.0-alpha-2` then `yom:yom:1.0-alpha-1`, and nothing else.
+pub const TINY: &[u8] = include_bytes!("../tiny-file");
+
+pub fn id(value: &str) -> UniqId {
+    value.parse().expect("valid in the test")
+}
+
+/// The docs in [`TINY`].
+pub fn docs() -> Vec<Doc> {
+    only_docs(
+        IndexReader::new(TINY)
+            .expect("valid header")
+            .map(|event| event.expect("valid records")),
+    )
+}
+
+/// Panics if there's anything but docs.
+pub fn only_docs(events: impl IntoIterator<Item = Event>) -> Vec<Doc> {
+    events
+        .into_iter()
+        .map(|event| match event {
+            Event::Doc(doc) => doc,
+            other => panic!("unexpected: {other:?}"),
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use std::io::Read;
+
+use anyhow::Result;
+
+use nexers::compression::decompress;
+use nexers::compression::Compression;
+use nexers::nexus::Event;
+
+use common::TINY;
+
+fn ids(data: &[u8]) -> Result<Vec<String>> {
+    let mut ret = Vec::new();
+    nexers::nexus::read_any(data, |event| {
+        match event {
+            Event::Doc(doc) => ret.push(format!("{:?}", doc.id)),
+            other => panic!("unexpected: {other:?}"),
+        }
+        Ok(())
+    })?;
+    Ok(ret)
+}
+
+#[test]
+fn uncompressed() -> Result<()> {
+    assert_eq!(Compression::None, Compression::sniff(TINY));
+    let mut unpacked = Vec::new();
+    decompress(TINY)?.read_to_end(&mut unpacked)?;
+    assert_eq!(TINY, unpacked.as_slice());
+    assert_eq!(2, ids(TINY)?.len());
+
+    // shorter than any magic
+    let mut unpacked = Vec::new();
+    decompress(&[1u8][..])?.read_to_end(&mut unpacked)?;
+    assert_eq!(vec![1u8], unpacked);
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip() -> Result<()> {
+    use std::io::Write;
+
+    let mut packed = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    packed.write_all(TINY)?;
+    let packed = packed.finish()?;
+
+    assert_eq!(Compression::Gzip, Compression::sniff(&packed));
+    assert_eq!(ids(TINY)?, ids(&packed)?);
+    Ok(())
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn gzip_disabled() {
+    let err = decompress(&[0x1f, 0x8b, 8, 0][..])
+        .err()
+        .expect("no gzip support");
+    assert!(err.to_string().contains("Gzip"), "{err}");
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd() -> Result<()> {
+    let packed = zstd::encode_all(TINY, 0)?;
+
+    assert_eq!(Compression::Zstd, Compression::sniff(&packed));
+    assert_eq!(ids(TINY)?, ids(&packed)?);
+    Ok(())
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn xz() -> Result<()> {
+    let mut packed = Vec::new();
+    xz2::read::XzEncoder::new(TINY, 6).read_to_end(&mut packed)?;
+
+    assert_eq!(Compression::Xz, Compression::sniff(&packed));
+    assert_eq!(ids(TINY)?, ids(&packed)?);
+    Ok(())
+}
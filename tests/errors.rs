@@ -0,0 +1,88 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::Writer;
+use nexers::Error;
+use nexers::Location;
+
+use common::TINY;
+
+#[test]
+fn truncated() -> Result<()> {
+    let mut reader = IndexReader::new(&TINY[..TINY.len() - 3])?;
+    assert!(matches!(reader.next(), Some(Ok(Event::Doc(_)))));
+
+    match reader.next() {
+        Some(Err(Error::Truncated { location })) => assert_eq!(
+            Location {
+                record: Some(1),
+                field: Some(3),
+                offset: 0x137,
+            },
+            location
+        ),
+        other => panic!("unexpected: {other:?}"),
+    }
+    assert!(reader.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn bad_header() {
+    match IndexReader::new(&[2u8; 9][..]) {
+        Err(Error::BadHeader { version, location }) => {
+            assert_eq!(2, version);
+            assert_eq!(None, location.record);
+        }
+        Err(other) => panic!("unexpected: {other:?}"),
+        Ok(_) => panic!("accepted a bad header"),
+    }
+}
+
+#[test]
+fn bad_fields() -> Result<()> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_fields(&[
+        (Name::U, "yom|yom|1.0|NA".to_string()),
+        (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+        (Name::M, "soon".to_string()),
+    ])?;
+    writer.write_fields(&[
+        (Name::U, "yom".to_string()),
+        (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+        (Name::M, "1318434018114".to_string()),
+    ])?;
+    writer.write_fields(&[(Name::N, "just a name".to_string())])?;
+    let written = writer.into_inner()?;
+
+    let errors = IndexReader::new(written.as_slice())?
+        .map(|event| match event {
+            Ok(Event::Error { error, .. }) => error,
+            other => panic!("unexpected: {other:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    match &errors[0] {
+        Error::MalformedModified { value, location } => {
+            assert_eq!("soon", value);
+            assert_eq!((Some(0), Some(2)), (location.record, location.field));
+        }
+        other => panic!("unexpected: {other:?}"),
+    }
+    match &errors[1] {
+        Error::MalformedUniq {
+            reason, location, ..
+        } => {
+            assert_eq!("short uniq: artifact", *reason);
+            assert_eq!((Some(1), Some(0)), (location.record, location.field));
+        }
+        other => panic!("unexpected: {other:?}"),
+    }
+    assert!(matches!(errors[2], Error::UnrecognisedRecord { .. }));
+    assert_eq!(3, errors.len());
+    Ok(())
+}
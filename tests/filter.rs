@@ -0,0 +1,93 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::Writer;
+use nexers::Error;
+
+use common::TINY;
+
+fn written() -> Result<Vec<u8>> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_descriptor("1.0", "example")?;
+    for (group, artifact) in [("wanted", "one"), ("other", "two"), ("wanted", "three")] {
+        writer.write_fields(&[
+            (Name::U, format!("{group}|{artifact}|1.0|NA")),
+            (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+            (Name::M, "1318434018114".to_string()),
+        ])?;
+    }
+    writer.write_fields(&[(Name::Other("del".into()), "wanted|gone|1.0|NA".to_string())])?;
+    writer.write_fields(&[(Name::Other("del".into()), "other|gone|1.0|NA".to_string())])?;
+    writer.write_fields(&[
+        (Name::U, "broken".to_string()),
+        (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+        (Name::M, "1318434018114".to_string()),
+    ])?;
+    writer.into_inner()
+}
+
+fn describe(event: Result<Event, Error>) -> String {
+    match event {
+        Ok(Event::Doc(doc)) => format!("doc {}", doc.id.artifact),
+        Ok(Event::Delete(id)) => format!("del {}", id.artifact),
+        Ok(Event::Error {
+            error: Error::MalformedUniq { location, .. },
+            ..
+        }) => format!("bad uniq in record {:?}", location.record),
+        other => panic!("unexpected: {other:?}"),
+    }
+}
+
+#[test]
+fn parsed() -> Result<()> {
+    let data = written()?;
+    let events = IndexReader::new(data.as_slice())?
+        .filter_uniq(|id| id.group == "wanted")
+        .map(describe)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            "doc one",
+            "doc three",
+            "del gone",
+            "bad uniq in record Some(6)",
+        ],
+        events
+    );
+    Ok(())
+}
+
+#[test]
+fn raw() -> Result<()> {
+    let data = written()?;
+    let events = IndexReader::new(data.as_slice())?
+        .filter_uniq_raw(|u| u.starts_with("other|"))
+        .map(describe)
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["doc two", "del gone"], events);
+    Ok(())
+}
+
+#[test]
+fn matches_decoding_everything() -> Result<()> {
+    let everything = IndexReader::new(TINY)?
+        .map(|event| match event {
+            Ok(Event::Doc(doc)) => Ok(doc),
+            other => panic!("unexpected: {other:?}"),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let last = everything.last().expect("non-empty").id.clone();
+
+    let filtered = IndexReader::new(TINY)?
+        .filter_uniq(|id| id.artifact == last.artifact && id.version == last.version)
+        .collect::<Result<Vec<_>, _>>()?;
+    match filtered.as_slice() {
+        [Event::Doc(doc)] => assert_eq!(everything.last(), Some(doc)),
+        other => panic!("unexpected: {other:?}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+
+use anyhow::Result;
+
+use nexers::identify::identify;
+use nexers::identify::sha1;
+use nexers::identify::ChecksumMap;
+use nexers::identify::Identified;
+use nexers::nexus::Doc;
+use nexers::nexus::Event;
+use nexers::nexus::UniqId;
+use nexers::nexus::Writer;
+
+use common::docs;
+use common::id;
+
+/// The tiny-file's docs, but with checksums of `hello` and `world`.
+fn checksummed() -> Result<Vec<Doc>> {
+    let mut docs = docs();
+    for (doc, content) in docs.iter_mut().zip([&b"hello"[..], b"world"]) {
+        doc.checksum = Some(sha1(content)?);
+    }
+    Ok(docs)
+}
+
+/// `a/hello`, `a/b/world` and `unknown`
+fn tree(name: &str) -> Result<PathBuf> {
+    let root = std::env::temp_dir().join(format!("nexers-{name}-{}", process::id()));
+    fs::create_dir_all(root.join("a/b"))?;
+    fs::write(root.join("a/hello"), "hello")?;
+    fs::write(root.join("a/b/world"), "world")?;
+    fs::write(root.join("unknown"), "unknown")?;
+    Ok(root)
+}
+
+fn check(root: &Path, found: Vec<Identified>) {
+    let found = found
+        .into_iter()
+        .map(|found| (found.path, found.matches))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (root.join("a/b/world"), vec![id("yom:yom:1.0-alpha-1@jar")]),
+            (root.join("a/hello"), vec![id("yom:yom:1.0-alpha-2@jar")]),
+            (root.join("unknown"), vec![]),
+        ],
+        found
+    );
+}
+
+#[test]
+fn sha1_of() -> Result<()> {
+    assert_eq!(
+        "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d",
+        hex::encode(sha1(&b"hello"[..])?)
+    );
+    Ok(())
+}
+
+#[test]
+fn in_memory() -> Result<()> {
+    let mut index = Writer::new(Vec::new(), 0)?;
+    for doc in checksummed()? {
+        index.write(&Event::Doc(doc))?;
+    }
+    let map = ChecksumMap::from_index(&index.into_inner()?[..])?;
+    assert_eq!(2, map.len());
+
+    let root = tree("identify-map")?;
+    let found = identify(&root, |checksum| {
+        Ok(map
+            .get(checksum)
+            .iter()
+            .map(|id| UniqId {
+                extension: Some("jar".into()),
+                ..id.clone()
+            })
+            .collect())
+    });
+    let single = identify(root.join("a/hello"), |checksum| {
+        Ok(map.get(checksum).to_vec())
+    });
+    fs::remove_dir_all(&root)?;
+
+    check(&root, found?);
+    let single = single?;
+    assert_eq!(1, single.len());
+    assert_eq!(sha1(&b"hello"[..])?, single[0].checksum);
+    assert_eq!(vec![id("yom:yom:1.0-alpha-2")], single[0].matches);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+#[test]
+fn db() -> Result<()> {
+    use nexers::db;
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    {
+        let mut builder = db::DbBuilder::new(&conn)?;
+        for doc in checksummed()? {
+            builder.add(&doc)?;
+        }
+    }
+    db::index_checksums(&conn)?;
+
+    let root = tree("identify-db")?;
+    let found = identify(&root, |checksum| db::find_by_checksum(&conn, checksum));
+    fs::remove_dir_all(&root)?;
+    check(&root, found?);
+    Ok(())
+}
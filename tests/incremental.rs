@@ -0,0 +1,100 @@
+use std::fs;
+
+use anyhow::Result;
+
+use nexers::incremental::chunk_file_name;
+use nexers::incremental::pending_chunks;
+use nexers::incremental::IndexProperties;
+use nexers::incremental::PROPERTIES_FILE;
+
+const SAMPLE: &str = r"#Tue Feb 27 01:25:09 UTC 2024
+nexus.index.incremental-0=815
+nexus.index.incremental-1=814
+nexus.index.incremental-2=813
+nexus.index.incremental-3=812
+nexus.index.chain-id=1318453614498
+nexus.index.last-incremental=815
+nexus.index.id=central
+nexus.index.timestamp=20240227012509.123 +0000
+nexus.index.time\:stamp=ignored
+";
+
+#[test]
+fn properties() -> Result<()> {
+    let props = IndexProperties::read(SAMPLE.as_bytes())?;
+    assert_eq!(Some("central"), props.repository_id.as_deref());
+    assert_eq!(Some("1318453614498"), props.chain_id.as_deref());
+    assert_eq!(Some(1_708_997_109_123), props.timestamp_ms);
+    assert_eq!(Some(815), props.last_incremental);
+    assert_eq!(vec![812, 813, 814, 815], props.incrementals);
+
+    assert_eq!(vec![814, 815], props.pending(813)?);
+    assert_eq!(Vec::<u32>::new(), props.pending(815)?);
+    assert_eq!(vec![812, 813, 814, 815], props.pending(811)?);
+    assert!(props.pending(810).is_err(), "811 is no longer published");
+    assert!(props.pending(900).is_err(), "from the future");
+
+    let last = IndexProperties {
+        last_incremental: Some(u32::MAX),
+        incrementals: vec![u32::MAX],
+        ..props
+    };
+    assert_eq!(Vec::<u32>::new(), last.pending(u32::MAX)?);
+    Ok(())
+}
+
+#[test]
+fn directory() -> Result<()> {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("incremental-directory");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(PROPERTIES_FILE), SAMPLE)?;
+
+    for number in [813, 815] {
+        fs::write(dir.join(chunk_file_name(number)), [])?;
+    }
+    assert!(pending_chunks(&dir, 812).is_err(), "814 is missing");
+
+    fs::write(dir.join(chunk_file_name(814)), [])?;
+    let chunks = pending_chunks(&dir, 812)?;
+    assert_eq!(
+        vec![813, 814, 815],
+        chunks.iter().map(|c| c.number).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        dir.join("nexus-maven-repository-index.814.gz"),
+        chunks[1].path
+    );
+    Ok(())
+}
+
+#[test]
+fn bad_timestamps() {
+    for timestamp in [
+        &b"20240227012509.123"[..],
+        b"2024022701250.123 +0000",
+        b"20240227012509.123 0000",
+        b"20240227012509.123 +000",
+        b"20240227012509.-12 +0000",
+        b"20240227012509.123 +0\xc3\xa90",
+        b"20240227012509.123 +\xc3\xa900",
+        b"20240227012509.9223372036854775807 +0000",
+        b"20240227012509.1234 +0000",
+        b"20241327012509.123 +0000",
+        b"20240200012509.123 +0000",
+        b"20240232012509.123 +0000",
+        b"20240227242509.123 +0000",
+        b"20240227016009.123 +0000",
+        b"20240227012560.123 +0000",
+        b"20240227012509.123 +2400",
+        b"20240227012509.123 +0060",
+    ] {
+        let mut file = b"nexus.index.timestamp=".to_vec();
+        file.extend_from_slice(timestamp);
+        assert!(
+            IndexProperties::read(&file[..]).is_err(),
+            "{}",
+            String::from_utf8_lossy(timestamp)
+        );
+    }
+}
@@ -0,0 +1,377 @@
+#![cfg(feature = "db")]
+
+mod common;
+
+use std::fs;
+use std::process;
+
+use anyhow::Result;
+
+use nexers::db;
+use nexers::db::IngestStats;
+use nexers::incremental::chunk_file_name;
+use nexers::incremental::Chunk;
+use nexers::incremental::IndexProperties;
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::Writer;
+
+use common::docs;
+use common::id;
+use common::TINY;
+
+#[test]
+fn deletes() -> Result<()> {
+    let mut index = Writer::new(Vec::new(), 0)?;
+    for event in IndexReader::new(TINY)? {
+        index.write(&event?)?;
+    }
+    for delete in [
+        // not there yet
+        "yom:yom:1.0-alpha-3",
+        // wrong classifier, or extension
+        "yom:yom:1.0-alpha-2:sources",
+        "yom:yom:1.0-alpha-2@pom",
+        // no extension, as in old indexes
+        "yom:yom:1.0-alpha-2",
+        // deleted already
+        "yom:yom:1.0-alpha-2@jar",
+    ] {
+        index.write(&Event::Delete(id(delete)))?;
+    }
+    let index = index.into_inner()?;
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    let (conn, stats) = db::ingest(&index[..], conn)?;
+
+    assert_eq!(
+        IngestStats {
+            added: 2,
+            replaced: 0,
+            older: 0,
+            deleted: 1,
+            unmatched_deletes: 4,
+            errors: 0,
+        },
+        stats
+    );
+    assert_eq!(vec!["1.0-alpha-1"], db::find_versions(&conn, "yom", "yom")?);
+
+    let mut index = Writer::new(Vec::new(), 0)?;
+    index.write(&Event::Delete(id("yom:yom:1.0-alpha-1@jar")))?;
+    let (conn, stats) = db::ingest(&index.into_inner()?[..], conn)?;
+    assert_eq!(
+        IngestStats {
+            added: 0,
+            replaced: 0,
+            older: 0,
+            deleted: 1,
+            unmatched_deletes: 0,
+            errors: 0,
+        },
+        stats
+    );
+    assert!(db::find_versions(&conn, "yom", "yom")?.is_empty());
+    Ok(())
+}
+
+fn names(conn: &rusqlite::Connection) -> Result<Vec<(String, Option<String>)>> {
+    Ok(conn
+        .prepare(
+            r"
+select version, (select name from name_names where id=name_id) from versions
+order by version, 2",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+#[test]
+fn update() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    let (conn, _) = db::ingest(TINY, conn)?;
+    assert_eq!(None, db::last_chunk(&conn)?);
+    db::set_last_chunk(&conn, 6)?;
+    assert_eq!(Some(6), db::last_chunk(&conn)?);
+
+    // a rename of alpha-2, and a new alpha-3
+    let docs = docs();
+    let mut renamed = docs[0].clone();
+    renamed.name = Some("renamed".to_string());
+    let mut added = docs[0].clone();
+    added.id.version = "1.0-alpha-3".into();
+
+    let mut seven = Writer::new(Vec::new(), 0)?;
+    seven.write(&Event::Doc(renamed))?;
+    seven.write(&Event::Doc(added))?;
+
+    let mut eight = Writer::new(Vec::new(), 0)?;
+    eight.write(&Event::Delete(id("yom:yom:1.0-alpha-1@jar")))?;
+    eight.write(&Event::Delete(id("yom:yom:1.0-alpha-4@jar")))?;
+
+    // not compressed, but that's fine
+    let dir = std::env::temp_dir().join(format!("nexers-update-{}", process::id()));
+    fs::create_dir_all(&dir)?;
+    let mut chunks = Vec::new();
+    for (number, chunk) in [(7, seven), (8, eight)] {
+        let path = dir.join(chunk_file_name(number));
+        fs::write(&path, chunk.into_inner()?)?;
+        chunks.push(Chunk {
+            number,
+            path,
+            chain_id: Some("chain".to_string()),
+        });
+    }
+    let updated = db::update(conn, &chunks);
+    fs::remove_dir_all(&dir)?;
+    let (conn, stats) = updated?;
+
+    assert_eq!(
+        IngestStats {
+            added: 1,
+            replaced: 1,
+            older: 0,
+            deleted: 1,
+            unmatched_deletes: 1,
+            errors: 0,
+        },
+        stats
+    );
+    assert_eq!(Some(8), db::last_chunk(&conn)?);
+    assert_eq!(Some("chain".to_string()), db::chain_id(&conn)?);
+    assert_eq!(
+        vec![
+            ("1.0-alpha-2".to_string(), Some("renamed".to_string())),
+            ("1.0-alpha-3".to_string(), Some("YOM".to_string())),
+        ],
+        names(&conn)?
+    );
+    Ok(())
+}
+
+#[test]
+fn update_checks_chunks() -> Result<()> {
+    let at_six = || -> Result<rusqlite::Connection> {
+        let (conn, _) = db::ingest(TINY, rusqlite::Connection::open_in_memory()?)?;
+        db::set_last_chunk(&conn, 6)?;
+        db::set_chain_id(&conn, "chain")?;
+        Ok(conn)
+    };
+
+    let dir = std::env::temp_dir().join(format!("nexers-update-checks-{}", process::id()));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(chunk_file_name(7));
+    fs::write(&path, Writer::new(Vec::new(), 0)?.into_inner()?)?;
+    let chunk = |number, chain_id: &str| Chunk {
+        number,
+        path: path.clone(),
+        chain_id: Some(chain_id.to_string()),
+    };
+
+    let checked = (|| -> Result<()> {
+        for (bad, expected) in [
+            (chunk(6, "chain"), "doesn't follow"),
+            (chunk(8, "chain"), "doesn't follow"),
+            (chunk(7, "other"), "from chain"),
+        ] {
+            let err = db::update(at_six()?, &[bad]).expect_err("applied a bad chunk");
+            assert!(err.to_string().contains(expected), "{err:?}");
+        }
+        let (conn, _) = db::update(at_six()?, &[chunk(7, "chain")])?;
+        assert_eq!(Some(7), db::last_chunk(&conn)?);
+        Ok(())
+    })();
+    fs::remove_dir_all(&dir)?;
+    checked
+}
+
+#[test]
+fn duplicates() -> Result<()> {
+    let docs = docs();
+    let mut older = docs[0].clone();
+    older.name = Some("older".to_string());
+    older.object_info.last_modified -= 1_000;
+    let mut newer = docs[0].clone();
+    newer.name = Some("newer".to_string());
+    newer.object_info.last_modified += 1_000;
+    let mut pom = docs[0].clone();
+    pom.object_info.extension = "pom".into();
+
+    let mut index = Writer::new(Vec::new(), 0)?;
+    for doc in docs.iter().chain([&newer, &older, &pom]) {
+        index.write(&Event::Doc(doc.clone()))?;
+    }
+    let index = index.into_inner()?;
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    let (conn, stats) = db::ingest(&index[..], conn)?;
+    assert_eq!(
+        IngestStats {
+            added: 3,
+            replaced: 1,
+            older: 1,
+            deleted: 0,
+            unmatched_deletes: 0,
+            errors: 0,
+        },
+        stats
+    );
+    let expected = vec![
+        ("1.0-alpha-1".to_string(), None),
+        ("1.0-alpha-2".to_string(), Some("YOM".to_string())),
+        ("1.0-alpha-2".to_string(), Some("newer".to_string())),
+    ];
+    assert_eq!(expected, names(&conn)?);
+
+    // the same again changes nothing; the first alpha-2 is now older, too
+    let (conn, stats) = db::ingest(&index[..], conn)?;
+    assert_eq!(
+        IngestStats {
+            added: 0,
+            replaced: 3,
+            older: 2,
+            deleted: 0,
+            unmatched_deletes: 0,
+            errors: 0,
+        },
+        stats
+    );
+    assert_eq!(expected, names(&conn)?);
+    Ok(())
+}
+
+#[test]
+fn legacy_duplicates() -> Result<()> {
+    // as before the unique index, where the same artifact could be added twice
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    for (version, last_modified) in [("1.0", 10), ("1.0", 30), ("1.0", 20), ("2.0", 40)] {
+        conn.execute(
+            r"
+insert into versions (group_id, artifact_id, version, extension_id, last_modified)
+  values (1, 1, ?, 1, ?)",
+            rusqlite::params![version, last_modified],
+        )?;
+    }
+
+    db::DbBuilder::open(&conn)?;
+    let rows = conn
+        .prepare("select version, last_modified from versions order by id")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(String, i64)>, _>>()?;
+    assert_eq!(vec![("1.0".to_string(), 30), ("2.0".to_string(), 40)], rows);
+    Ok(())
+}
+
+#[test]
+fn writer_error() -> Result<()> {
+    // well past the channel's capacity, so the reader is still sending when the writer fails
+    let mut index = Writer::new(Vec::new(), 0)?;
+    let mut doc = docs().remove(0);
+    for version in 0..200_000 {
+        doc.id.version = version.to_string().into();
+        index.write(&Event::Doc(doc.clone()))?;
+    }
+    let index = index.into_inner()?;
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    db::migrate(&conn)?;
+    conn.execute_batch(
+        r"
+create trigger versions_full before insert on versions
+when (select count(*) from versions) >= 1000
+begin select raise(abort, 'versions is full'); end",
+    )?;
+
+    let err = db::ingest(&index[..], conn).expect_err("the trigger fires");
+    assert!(format!("{err:#}").contains("versions is full"), "{err:#}");
+    Ok(())
+}
+
+#[test]
+fn meta_read_only() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("nexers-meta-{}.db", process::id()));
+    rusqlite::Connection::open(&path)?.execute_batch(db::SCHEMA)?;
+
+    let read = (|| -> Result<()> {
+        let conn = rusqlite::Connection::open_with_flags(
+            &path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?;
+        assert!(db::read_meta(&conn)?.is_empty());
+        assert_eq!(None, db::last_chunk(&conn)?);
+        assert_eq!(None, db::chain_id(&conn)?);
+        Ok(())
+    })();
+    fs::remove_file(&path)?;
+    read
+}
+
+#[test]
+fn meta() -> Result<()> {
+    let mut index = Writer::new(Vec::new(), 1234)?;
+    index.write(&Event::Descriptor {
+        version: "1.0".to_string(),
+        repository_id: "central".to_string(),
+    })?;
+    for event in IndexReader::new(TINY)? {
+        index.write(&event?)?;
+    }
+    index.write(&Event::Delete(id("yom:yom:1.0-alpha-1@jar")))?;
+    // skipped, and counted
+    index.write_fields(&[(Name::N, "lonely".to_string())])?;
+    let index = index.into_inner()?;
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    assert!(db::read_meta(&conn)?.is_empty());
+    let (conn, stats) = db::ingest(&index[..], conn)?;
+    assert_eq!(1, stats.errors);
+
+    let meta = db::read_meta(&conn)?;
+    assert_eq!(
+        Some(env!("CARGO_PKG_VERSION")),
+        meta.get("nexers_version").map(String::as_str)
+    );
+    assert!(meta.contains_key("ingested_at"));
+    for (key, value) in [
+        ("index_timestamp", "1234"),
+        ("repository_id", "central"),
+        ("docs", "2"),
+        ("deletes", "1"),
+        ("errors", "1"),
+        ("total_docs", "2"),
+    ] {
+        assert_eq!(Some(value), meta.get(key).map(String::as_str), "{key}");
+    }
+
+    // the counts are of the last ingest, and the totals over every one
+    let (conn, _) = db::ingest(&index[..], conn)?;
+    db::set_properties(
+        &conn,
+        &IndexProperties {
+            chain_id: Some("chain".to_string()),
+            last_incremental: Some(5),
+            ..IndexProperties::default()
+        },
+    )?;
+    let meta = db::read_meta(&conn)?;
+    for (key, value) in [
+        ("docs", "2"),
+        ("deletes", "1"),
+        ("errors", "1"),
+        ("total_docs", "4"),
+        ("total_deletes", "2"),
+        ("total_errors", "2"),
+        ("chain_id", "chain"),
+        ("last_chunk", "5"),
+    ] {
+        assert_eq!(Some(value), meta.get(key).map(String::as_str), "{key}");
+    }
+    assert_eq!(Some(5), db::last_chunk(&conn)?);
+    Ok(())
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::layout;
+use nexers::nexus::FullInfo;
+
+use common::docs;
+use common::id;
+
+fn info() -> FullInfo {
+    docs().remove(0).object_info
+}
+
+#[test]
+fn paths() -> Result<()> {
+    let docs = docs();
+    let doc = &docs[0];
+
+    // `u` has no extension, and `NA` for no classifier
+    assert_eq!(None, doc.id.extension);
+    assert_eq!(None, doc.id.classifier);
+    assert_eq!("yom/yom/1.0-alpha-2", layout::directory(&doc.id));
+    assert_eq!(
+        "yom/yom/1.0-alpha-2/yom-1.0-alpha-2.jar",
+        layout::path(&doc.id, &doc.object_info)
+    );
+    assert_eq!(
+        "yom/yom/1.0-alpha-2/yom-1.0-alpha-2.jar.sha1",
+        layout::sha1_path(&doc.id, &doc.object_info)
+    );
+    assert_eq!(
+        "yom/yom/1.0-alpha-2/yom-1.0-alpha-2.jar.asc",
+        layout::asc_path(&doc.id, &doc.object_info)
+    );
+    assert_eq!(
+        "yom/yom/1.0-alpha-2/yom-1.0-alpha-2.pom",
+        layout::pom_path(&doc.id)
+    );
+
+    let mut info = doc.object_info.clone();
+    let sources = id("org.apache.foo:bar:1.0:sources@jar");
+    assert_eq!(
+        "org/apache/foo/bar/1.0/bar-1.0-sources.jar",
+        layout::path(&sources, &info)
+    );
+    assert_eq!(
+        "org/apache/foo/bar/1.0/bar-1.0.pom",
+        layout::pom_path(&sources)
+    );
+
+    // no extension anywhere, so it's from the packaging
+    info.extension = "".into();
+    info.packaging = "maven-plugin".into();
+    assert_eq!(
+        "org/example/plug/2/plug-2.jar",
+        layout::path(&id("org.example:plug:2"), &info)
+    );
+    info.packaging = "war".into();
+    assert_eq!(
+        "org/example/web/2/web-2.war",
+        layout::path(&id("org.example:web:2"), &info)
+    );
+    Ok(())
+}
+
+#[test]
+fn snapshots() -> Result<()> {
+    let timestamped = id("org.example:example:1.0-20240101.123456-12:tests@jar");
+    assert_eq!(
+        "org/example/example/1.0-SNAPSHOT",
+        layout::directory(&timestamped)
+    );
+    let path = "org/example/example/1.0-SNAPSHOT/example-1.0-20240101.123456-12-tests.jar";
+    assert_eq!(path, layout::path(&timestamped, &info()));
+    assert_eq!(timestamped, layout::parse_path(path)?);
+
+    assert_eq!(
+        id("org.example:example:1.0-SNAPSHOT@jar"),
+        layout::parse_path("org/example/example/1.0-SNAPSHOT/example-1.0-SNAPSHOT.jar")?
+    );
+    assert_eq!(
+        id("org.example:example:1.0-20240101.123456-1@pom"),
+        layout::parse_path("org/example/example/1.0-SNAPSHOT/example-1.0-20240101.123456-1.pom")?
+    );
+    Ok(())
+}
+
+#[test]
+fn parsing() -> Result<()> {
+    for value in [
+        "org.apache.foo:bar:1.0@jar",
+        "org.apache.foo:bar:1.0:sources@jar",
+        "org.apache.foo:bar:1.0@tar.gz",
+        "org.apache.foo:bar:1.0:jdk15@jar.sha1",
+        "org:bar-baz:1.0-rc-1:linux-x86_64@so",
+    ] {
+        let id = id(value);
+        let extension = id.extension.as_deref().expect("all have extensions");
+        let mut info = info();
+        info.extension = extension.into();
+        let path = layout::path(&id, &info);
+        assert_eq!(id, layout::parse_path(&path)?, "{path}");
+        assert_eq!(id, layout::parse_path(&format!("/{path}"))?, "{path}");
+    }
+    Ok(())
+}
+
+#[test]
+fn bad_paths() {
+    for path in [
+        "",
+        "bar/1.0/bar-1.0.jar",
+        "org//bar/1.0/bar-1.0.jar",
+        "org/bar/1.0/baz-1.0.jar",
+        "org/bar/1.0/bar-1.1.jar",
+        "org/bar/1.0/bar-1.0",
+        "org/bar/1.0/bar-1.0.",
+        "org/bar/1.0/bar-1.0-.jar",
+        "org/bar/1.0/bar-1.0x.jar",
+        "org/x/bar/1.0/bar-1.0.1.jar",
+        "org/x/bar/1.0/bar-1.0.1-sources.jar",
+        "org/bar/1.0/bar-1.0..jar",
+    ] {
+        assert!(layout::parse_path(path).is_err(), "{path:?}");
+    }
+}
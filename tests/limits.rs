@@ -0,0 +1,95 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::nexus::IndexReader;
+use nexers::nexus::ReaderOptions;
+use nexers::Error;
+
+use common::TINY;
+
+fn header() -> Vec<u8> {
+    TINY[..9].to_vec()
+}
+
+fn first_error(data: &[u8], options: ReaderOptions) -> Error {
+    let reader = IndexReader::with_options(data, options).expect("header");
+    for event in reader {
+        if let Err(e) = event {
+            return e;
+        }
+    }
+    panic!("no error");
+}
+
+#[test]
+fn huge_field_count() {
+    let mut data = header();
+    data.extend_from_slice(&i32::MAX.to_be_bytes());
+    match first_error(&data, ReaderOptions::default()) {
+        Error::LimitExceeded { what, len, .. } => {
+            assert_eq!("field count", what);
+            assert_eq!(i32::MAX as usize, len);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn huge_value() {
+    let mut data = header();
+    data.extend_from_slice(&1i32.to_be_bytes());
+    data.extend_from_slice(&[5, 0, 1, b'u']);
+    data.extend_from_slice(&i32::MAX.to_be_bytes());
+    data.extend_from_slice(b"short");
+    match first_error(&data, ReaderOptions::default()) {
+        Error::LimitExceeded { what, .. } => assert_eq!("field value", what),
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let options = ReaderOptions {
+        max_value_len: usize::MAX,
+        ..ReaderOptions::default()
+    };
+    match first_error(&data, options) {
+        Error::LimitExceeded { what, .. } => assert_eq!("record", what),
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let options = ReaderOptions {
+        max_value_len: usize::MAX,
+        max_record_size: usize::MAX,
+        ..ReaderOptions::default()
+    };
+    // we get as far as reading the data, but don't allocate it up front
+    assert!(matches!(
+        first_error(&data, options),
+        Error::Truncated { .. }
+    ));
+}
+
+#[test]
+fn tight_limits() -> Result<()> {
+    let options = ReaderOptions {
+        max_fields: 6,
+        max_name_len: 1,
+        max_value_len: 40,
+        max_record_size: 22 + 13 + 33 + 3 + 35 + 40 + 6,
+        resync: false,
+    };
+    assert_eq!(2, IndexReader::with_options(TINY, options.clone())?.count());
+
+    let options = ReaderOptions {
+        max_record_size: options.max_record_size - 1,
+        ..options
+    };
+    match first_error(TINY, options) {
+        Error::LimitExceeded { what, location, .. } => {
+            assert_eq!("record", what);
+            assert_eq!(Some(0), location.record);
+            assert_eq!(Some(5), location.field);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    Ok(())
+}
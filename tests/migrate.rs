@@ -0,0 +1,132 @@
+#![cfg(feature = "db")]
+
+mod common;
+
+use anyhow::Result;
+
+use nexers::db;
+
+use common::docs;
+use common::TINY;
+
+fn vers(conn: &rusqlite::Connection) -> Result<Vec<(String, String, String)>> {
+    Ok(conn
+        .prepare("select artifact, version, last_modified from vers order by version, id")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+#[test]
+fn fresh() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+    assert_eq!(0, db::schema_version(&conn)?);
+    assert_eq!(0, db::migrate(&conn)?);
+    assert_eq!(db::SCHEMA_VERSION, db::schema_version(&conn)?);
+    assert_eq!(db::SCHEMA_VERSION, db::migrate(&conn)?);
+    // only the unique index, so an ingest doesn't maintain the others
+    let indexes = conn
+        .prepare("select name from sqlite_master where type='index' and tbl_name='versions'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    assert_eq!(vec!["versions_coordinates".to_string()], indexes);
+
+    let (conn, _) = db::ingest(TINY, conn)?;
+    assert_eq!(
+        vec![
+            (
+                "yom".to_string(),
+                "1.0-alpha-1".to_string(),
+                "2005-11-22 18:29:17".to_string()
+            ),
+            (
+                "yom".to_string(),
+                "1.0-alpha-2".to_string(),
+                "2005-11-22 18:29:17".to_string()
+            ),
+        ],
+        vers(&conn)?
+    );
+    Ok(())
+}
+
+#[test]
+fn legacy() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    // as by hand, from the old `workbook.sql`
+    conn.execute_batch("create index versions_ga on versions (group_id, artifact_id)")?;
+    // duplicates, from before the unique index; the newest is kept
+    for (version, classifier, last_modified) in [
+        ("1.0", None, 10),
+        ("1.0", None, 30),
+        ("1.0", None, 20),
+        ("1.0", Some(1), 5),
+        ("2.0", None, 40),
+    ] {
+        conn.execute(
+            r"
+insert into versions (group_id, artifact_id, version, classifier_id, extension_id, last_modified)
+  values (1, 1, ?, ?, 1, ?)",
+            rusqlite::params![version, classifier, last_modified],
+        )?;
+    }
+    assert_eq!(1, db::schema_version(&conn)?);
+
+    assert_eq!(1, db::migrate(&conn)?);
+    assert_eq!(db::SCHEMA_VERSION, db::schema_version(&conn)?);
+    let rows = conn
+        .prepare("select version, classifier_id, last_modified from versions order by id")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<(String, Option<i64>, i64)>, _>>()?;
+    assert_eq!(
+        vec![
+            ("1.0".to_string(), None, 30),
+            ("1.0".to_string(), Some(1), 5),
+            ("2.0".to_string(), None, 40),
+        ],
+        rows
+    );
+    assert!(db::read_meta(&conn)?.is_empty());
+
+    // and it's usable as normal
+    let mut builder = db::DbBuilder::open(&conn)?;
+    for doc in docs() {
+        assert_eq!(db::Added::Inserted, builder.add(&doc)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn newer() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+    db::migrate(&conn)?;
+    conn.pragma_update(None, "user_version", db::SCHEMA_VERSION + 1)?;
+    assert!(db::migrate(&conn).is_err());
+    assert!(db::DbBuilder::open(&conn).is_err());
+    Ok(())
+}
+
+#[test]
+fn atomic() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    for last_modified in [10, 20] {
+        conn.execute(
+            r"
+insert into versions (group_id, artifact_id, version, extension_id, last_modified)
+  values (1, 1, '1.0', 1, ?)",
+            [last_modified],
+        )?;
+    }
+    // in the way of the unique index, so its step fails, after deduplicating
+    conn.execute_batch("create table versions_coordinates (id integer)")?;
+    assert!(db::migrate(&conn).is_err());
+    assert_eq!(1, db::schema_version(&conn)?);
+    let rows: i64 = conn.query_row("select count(*) from versions", [], |row| row.get(0))?;
+    assert_eq!(2, rows);
+
+    conn.execute_batch("drop table versions_coordinates")?;
+    assert_eq!(1, db::migrate(&conn)?);
+    assert_eq!(db::SCHEMA_VERSION, db::schema_version(&conn)?);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::ParallelReader;
+use nexers::nexus::Writer;
+use nexers::Error;
+
+use common::TINY;
+
+/// Enough records for a few batches, with some which aren't docs.
+fn many() -> Result<Vec<u8>> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_descriptor("1.0", "example")?;
+    for i in 0..5_000 {
+        if i % 7 == 0 {
+            writer.write_fields(&[(Name::N, format!("no uniq {i}"))])?;
+            continue;
+        }
+        writer.write_fields(&[
+            (Name::U, format!("com.example|artifact-{i}|1.{i}|NA")),
+            (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+            (Name::M, format!("{}", 1318434018114u64 + i)),
+            (Name::D, "ünïcödé".to_string()),
+        ])?;
+    }
+    writer.into_inner()
+}
+
+fn describe(events: impl Iterator<Item = Result<Event, Error>>) -> Vec<String> {
+    events.map(|event| format!("{event:?}")).collect()
+}
+
+#[test]
+fn matches_sequential() -> Result<()> {
+    let data = many()?;
+    let sequential = describe(IndexReader::new(data.as_slice())?);
+    assert_eq!(5_001, sequential.len());
+
+    for threads in [1, 3, 8] {
+        let parallel = describe(ParallelReader::new(
+            IndexReader::new(data.as_slice())?,
+            threads,
+        ));
+        assert_eq!(sequential, parallel);
+    }
+
+    let tiny = describe(ParallelReader::new(IndexReader::new(TINY)?, 2));
+    assert_eq!(describe(IndexReader::new(TINY)?), tiny);
+    Ok(())
+}
+
+#[test]
+fn framing_error_is_last() -> Result<()> {
+    let mut data = many()?;
+    data.truncate(data.len() - 3);
+
+    let events = ParallelReader::new(IndexReader::new(data.as_slice())?, 4).collect::<Vec<_>>();
+    assert_eq!(5_001, events.len());
+    assert!(events[..5_000].iter().all(|event| event.is_ok()));
+    assert!(matches!(events[5_000], Err(Error::Truncated { .. })));
+    Ok(())
+}
+
+#[test]
+fn decoding_error_is_last() -> Result<()> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_fields(&[(Name::N, "fine".to_string())])?;
+    writer.write_fields(&[(Name::N, "\u{7f}".to_string())])?;
+    writer.write_fields(&[(Name::N, "unreachable".to_string())])?;
+    let mut data = writer.into_inner()?;
+
+    // the value of the second record is now a lone continuation byte
+    let marker = data.iter().position(|&b| b == 0x7f).expect("written");
+    data[marker] = 0x80;
+
+    let events = ParallelReader::new(IndexReader::new(data.as_slice())?, 2).collect::<Vec<_>>();
+    assert_eq!(2, events.len());
+    match &events[1] {
+        Err(Error::BadUtf8 { location, .. }) => {
+            assert_eq!((Some(1), Some(0)), (location.record, location.field))
+        }
+        other => panic!("unexpected: {other:?}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::purl;
+
+use common::id;
+
+#[test]
+fn to_purl() {
+    for (id, expected) in [
+        (
+            id("org.example:example:1.0"),
+            "pkg:maven/org.example/example@1.0",
+        ),
+        (
+            id("org.example:example:1.0@jar"),
+            "pkg:maven/org.example/example@1.0",
+        ),
+        (
+            id("org.example:example:1.0@pom"),
+            "pkg:maven/org.example/example@1.0?type=pom",
+        ),
+        (
+            id("org.example:example:1.0:sources@zip"),
+            "pkg:maven/org.example/example@1.0?classifier=sources&type=zip",
+        ),
+        (
+            id("org.example:example:1.0+build 2:linux/x86@tar.gz"),
+            "pkg:maven/org.example/example@1.0%2Bbuild%202?classifier=linux%2Fx86&type=tar.gz",
+        ),
+        (
+            id("org.example:ex\u{e4}mple:1.0"),
+            "pkg:maven/org.example/ex%C3%A4mple@1.0",
+        ),
+    ] {
+        let purl = purl::to_purl(&id);
+        assert_eq!(expected, purl);
+        let mut back = purl::parse(&purl).expect("round trip");
+        // `jar` is the default, so it's not in the purl
+        if back.extension.is_none() {
+            back.extension = id.extension.clone();
+        }
+        assert_eq!(id, back);
+    }
+}
+
+#[test]
+fn parse() -> Result<()> {
+    let expected = id("org.example:example:1.0:sources@zip");
+    for purl in [
+        "pkg:maven/org.example/example@1.0?type=zip&classifier=sources",
+        "PKG:Maven/org.example/example@1.0?classifier=sources&type=zip",
+        "pkg://maven/org.example/example@1.0?classifier=sources&type=zip#some/path",
+        "pkg:maven/org.example/example@1.0?repository_url=repo.example.org&classifier=sources&type=zip",
+        "pkg:maven/org%2Eexample/example@1%2E0?classifier=sources&type=z%69p",
+    ] {
+        assert_eq!(expected, purl::parse(purl)?, "{purl}");
+    }
+    assert_eq!(
+        id("org.example:example:1.0"),
+        purl::parse("pkg:maven/org.example/example@1.0?classifier=")?
+    );
+    Ok(())
+}
+
+#[test]
+fn bad() {
+    for purl in [
+        "",
+        "maven/org.example/example@1.0",
+        "pkg:npm/example@1.0",
+        "pkg:maven/example@1.0",
+        "pkg:maven/org/example/example@1.0",
+        "pkg:maven/org.example/example",
+        "pkg:maven/org.example/example@",
+        "pkg:maven/org.example/example@1.0%",
+        "pkg:maven/org.example/example@1.0%zz",
+        "pkg:maven/org.example/example@1.0%ff",
+        "pkg:maven/org.example/example@1.0?classifier",
+    ] {
+        assert!(purl::parse(purl).is_err(), "{purl:?}");
+    }
+}
+
+#[cfg(feature = "sql-functions")]
+#[test]
+fn sql() -> Result<()> {
+    use nexers::db;
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    conn.execute_batch(db::SCHEMA)?;
+    db::add_functions(&conn)?;
+    {
+        let mut builder = db::DbBuilder::new(&conn)?;
+        for doc in common::docs() {
+            assert_eq!(db::Added::Inserted, builder.add(&doc)?);
+        }
+    }
+
+    let purls = conn
+        .prepare(
+            r"
+select purl(g.name, a.name, version, c.name, e.name) from versions
+  join group_names g on g.id=group_id
+  join artifact_names a on a.id=artifact_id
+  left join classifier_names c on c.id=classifier_id
+  left join packaging_names e on e.id=extension_id
+order by version",
+        )?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    assert_eq!(
+        vec![
+            "pkg:maven/yom/yom@1.0-alpha-1",
+            "pkg:maven/yom/yom@1.0-alpha-2"
+        ],
+        purls
+    );
+
+    let purl: String = conn.query_row(
+        "select purl('org.example', 'example', '1.0', null, 'pom')",
+        [],
+        |row| row.get(0),
+    )?;
+    assert_eq!("pkg:maven/org.example/example@1.0?type=pom", purl);
+    let purl: String = conn.query_row("select purl('g', 'a', 'v', 'c')", [], |row| row.get(0))?;
+    assert_eq!("pkg:maven/g/a@v?classifier=c", purl);
+    Ok(())
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use std::borrow::Cow;
+
+use anyhow::Result;
+
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::ReaderOptions;
+use nexers::nexus::Writer;
+use nexers::Error;
+
+use common::docs;
+use common::TINY;
+
+#[test]
+fn matches_owned() -> Result<()> {
+    let owned = docs();
+
+    let mut reader = IndexReader::new(TINY)?;
+    let mut seen = 0;
+    while let Some(record) = reader.next_record()? {
+        let doc = record.doc()?.expect("only docs in the sample");
+        assert!(matches!(doc.id.group, Cow::Borrowed(_)));
+        assert_eq!(owned[seen].id, doc.id.to_uniq_id());
+        assert_eq!(owned[seen].checksum, doc.checksum);
+        assert_eq!(owned[seen], doc.to_doc()?);
+        assert!(matches!(record.get("u")?, Some(Cow::Borrowed(_))));
+        assert_eq!(None, record.get("nope")?);
+        seen += 1;
+    }
+    assert_eq!(owned.len(), seen);
+    Ok(())
+}
+
+#[test]
+fn not_docs() -> Result<()> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_descriptor("1.0", "example")?;
+    writer.write_fields(&[(Name::Other("del".into()), "a|b|1|NA|jar".to_string())])?;
+    writer.write_fields(&[
+        (Name::U, "yom|yom|1.0|NA".to_string()),
+        (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+        (Name::M, "soon".to_string()),
+    ])?;
+    let written = writer.into_inner()?;
+
+    let mut reader = IndexReader::new(written.as_slice())?;
+
+    let descriptor = reader.next_record()?.expect("descriptor");
+    assert!(descriptor.doc()?.is_none());
+    assert_eq!(
+        vec![
+            ("DESCRIPTOR".to_string(), "NexusIndex".to_string()),
+            ("IDXINFO".to_string(), "1.0|example".to_string()),
+        ],
+        descriptor
+            .fields()
+            .map(|field| field.map(|(k, v)| (k.into_owned(), v.into_owned())))
+            .collect::<Result<Vec<_>, _>>()?
+    );
+    assert!(matches!(descriptor.to_event()?, Event::Descriptor { .. }));
+
+    let delete = reader.next_record()?.expect("delete");
+    assert!(delete.doc()?.is_none());
+    assert!(matches!(delete.to_event()?, Event::Delete(_)));
+
+    let bad = reader.next_record()?.expect("bad");
+    match bad.doc() {
+        Err(Error::MalformedModified { value, location }) => {
+            assert_eq!("soon", value);
+            assert_eq!((Some(2), Some(2)), (location.record, location.field));
+        }
+        other => panic!("unexpected: {other:?}"),
+    }
+
+    assert!(reader.next_record()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn modified_utf8() -> Result<()> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_fields(&[
+        (Name::U, "com.example|emoji\u{1f600}|1.0|NA".to_string()),
+        (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+        (Name::M, "1318434018114".to_string()),
+    ])?;
+    let written = writer.into_inner()?;
+
+    let mut reader = IndexReader::new(written.as_slice())?;
+    let record = reader.next_record()?.expect("written");
+    let doc = record.doc()?.expect("a doc");
+    assert!(matches!(doc.id.artifact, Cow::Owned(_)));
+    assert_eq!("emoji\u{1f600}", doc.id.artifact);
+    Ok(())
+}
+
+#[test]
+fn resync_skips_are_not_fatal() -> Result<()> {
+    let mut data = TINY.to_vec();
+    // the second record's field count
+    data[0xcf..0xd3].copy_from_slice(&[0xff; 4]);
+    let options = ReaderOptions {
+        resync: true,
+        ..ReaderOptions::default()
+    };
+
+    let mut reader = IndexReader::with_options(data.as_slice(), options)?;
+    assert!(reader.next_record()?.is_some());
+    assert!(matches!(reader.next_record(), Err(Error::Skipped { .. })));
+    assert!(reader.next_record()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn junk_checksum() -> Result<()> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_fields(&[
+        (Name::U, "yom|yom|1.0|NA".to_string()),
+        (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+        (Name::M, "1132684157000".to_string()),
+        (Name::Checksum, "not a sha1".to_string()),
+    ])?;
+    let written = writer.into_inner()?;
+
+    // kept, without the checksum
+    match IndexReader::new(written.as_slice())?.next() {
+        Some(Ok(Event::Doc(doc))) => assert_eq!(None, doc.checksum),
+        other => panic!("unexpected: {other:?}"),
+    }
+    let mut reader = IndexReader::new(written.as_slice())?;
+    let record = reader.next_record()?.expect("a record");
+    assert_eq!(None, record.doc()?.expect("a doc").checksum);
+    Ok(())
+}
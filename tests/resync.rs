@@ -0,0 +1,116 @@
+use anyhow::Result;
+
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::ReaderOptions;
+use nexers::nexus::Writer;
+use nexers::Error;
+
+const ARTIFACTS: [&str; 3] = ["alpha", "beta", "gamma"];
+
+/// The header, then a doc for each of the first `records` artifacts.
+fn written(records: usize) -> Result<Vec<u8>> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    for artifact in &ARTIFACTS[..records] {
+        writer.write_fields(&[
+            (Name::U, format!("com.example|{artifact}|1.0|NA")),
+            (Name::I, "jar|1132684157000|52898|0|0|0|jar".to_string()),
+            (Name::M, "1318434018114".to_string()),
+        ])?;
+    }
+    writer.into_inner()
+}
+
+fn resync() -> ReaderOptions {
+    ReaderOptions {
+        resync: true,
+        ..ReaderOptions::default()
+    }
+}
+
+/// The artifact for docs, and the skipped range for errors.
+fn summarise(data: &[u8]) -> Result<Vec<String>> {
+    let mut ret = Vec::new();
+    for event in IndexReader::with_options(data, resync())? {
+        ret.push(match event? {
+            Event::Doc(doc) => doc.id.artifact.to_string(),
+            Event::Error {
+                error: Error::Skipped { skipped, .. },
+                raw,
+            } => {
+                assert!(raw.is_empty());
+                format!("{skipped:?}")
+            }
+            other => panic!("unexpected: {other:?}"),
+        });
+    }
+    Ok(ret)
+}
+
+#[test]
+fn bad_length() -> Result<()> {
+    let second = written(1)?.len();
+    let third = written(2)?.len();
+    let mut data = written(3)?;
+
+    // the length of the `u` value in the second record: count, flags, name length, name
+    let value_len = second + 4 + 1 + 2 + 1;
+    data[value_len..value_len + 4].copy_from_slice(&i32::MAX.to_be_bytes());
+
+    assert_eq!(
+        vec![
+            "alpha".to_string(),
+            format!("{second}..{third}"),
+            "gamma".to_string()
+        ],
+        summarise(&data)?
+    );
+
+    // without resync, that's the end
+    let events = IndexReader::new(data.as_slice())?.collect::<Vec<_>>();
+    assert_eq!(2, events.len());
+    assert!(matches!(events[1], Err(Error::LimitExceeded { .. })));
+    Ok(())
+}
+
+#[test]
+fn garbage_between_records() -> Result<()> {
+    let second = written(1)?.len();
+    let mut data = written(3)?;
+    data.splice(second..second, [0xff; 100]);
+
+    assert_eq!(
+        vec![
+            "alpha".to_string(),
+            format!("{second}..{}", second + 100),
+            "beta".to_string(),
+            "gamma".to_string()
+        ],
+        summarise(&data)?
+    );
+    Ok(())
+}
+
+#[test]
+fn truncated() -> Result<()> {
+    let mut data = written(3)?;
+    data.truncate(data.len() - 3);
+
+    let mut reader = IndexReader::with_options(data.as_slice(), resync())?;
+    assert!(matches!(reader.next(), Some(Ok(Event::Doc(_)))));
+    assert!(matches!(reader.next(), Some(Ok(Event::Doc(_)))));
+    match reader.next() {
+        Some(Ok(Event::Error {
+            error: Error::Skipped { skipped, cause, .. },
+            ..
+        })) => {
+            assert_eq!(data.len() as u64, skipped.end);
+            assert!(matches!(*cause, Error::Truncated { .. }));
+        }
+        other => panic!("unexpected: {other:?}"),
+    }
+    assert!(reader.next().is_none());
+    assert_eq!(3, reader.records());
+    Ok(())
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use anyhow::Result;
+use serde_json::json;
+
+use nexers::nexus::Doc;
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::Name;
+use nexers::nexus::Writer;
+
+use common::docs;
+
+#[test]
+fn shape() -> Result<()> {
+    let doc = docs().remove(0);
+    assert_eq!(
+        json!({
+            "type": "doc",
+            "id": {
+                "group": "yom",
+                "artifact": "yom",
+                "version": "1.0-alpha-2",
+                "classifier": null,
+                "extension": null,
+            },
+            "object_info": {
+                "packaging": "jar",
+                "last_modified": {
+                    "millis": 1132684157000u64,
+                    "rfc3339": "2005-11-22T18:29:17.000Z",
+                },
+                "size": 52898,
+                "source_attached": "absent",
+                "javadoc_attached": "absent",
+                "signature_attached": "absent",
+                "extension": "jar",
+            },
+            "modified": {
+                "millis": 1318434018114u64,
+                "rfc3339": "2011-10-12T15:40:18.114Z",
+            },
+            "name": "YOM",
+            "description": "Yet (Another Document) Object Model",
+            "checksum": "40b01f5b142540696d57e6673d0bdc5bdcce151a",
+            "class_names": null,
+            "plugin": null,
+            "osgi": null,
+            "extra": {},
+        }),
+        serde_json::to_value(Event::Doc(doc))?
+    );
+    Ok(())
+}
+
+#[test]
+fn round_trip() -> Result<()> {
+    for doc in docs() {
+        let json = serde_json::to_string(&doc)?;
+        assert_eq!(doc, serde_json::from_str::<Doc>(&json)?);
+
+        match serde_json::from_str(&serde_json::to_string(&Event::Doc(doc.clone()))?)? {
+            Event::Doc(again) => assert_eq!(doc, again),
+            other => panic!("unexpected: {other:?}"),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn minimal() -> Result<()> {
+    let doc: Doc = serde_json::from_value(json!({
+        "id": { "group": "g", "artifact": "a", "version": "1" },
+        "object_info": {
+            "packaging": "pom",
+            "last_modified": 951782400000u64,
+            "size": null,
+            "source_attached": "present",
+            "javadoc_attached": "unavailable",
+            "signature_attached": "absent",
+            "extension": "pom",
+        },
+        "modified": { "millis": 951782400000u64 },
+    }))?;
+    assert_eq!(951782400000, doc.modified);
+    assert_eq!(None, doc.checksum);
+    assert!(doc.extra.is_empty());
+
+    let value = serde_json::to_value(&doc)?;
+    assert_eq!(
+        json!("2000-02-29T00:00:00.000Z"),
+        value["object_info"]["last_modified"]["rfc3339"]
+    );
+    Ok(())
+}
+
+#[test]
+fn other_events() -> Result<()> {
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_descriptor("1.0", "central")?;
+    writer.write_fields(&[(Name::Other("del".into()), "g|a|1|NA|jar".to_string())])?;
+    writer.write_groups(nexers::nexus::GroupKind::Root, &["com", "org"])?;
+    writer.write_fields(&[(Name::N, "lonely".to_string())])?;
+    let written = writer.into_inner()?;
+
+    let events = IndexReader::new(written.as_slice())?
+        .map(|event| Ok(serde_json::to_value(event?)?))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![
+            json!({ "type": "descriptor", "version": "1.0", "repository_id": "central" }),
+            json!({
+                "type": "delete",
+                "group": "g",
+                "artifact": "a",
+                "version": "1",
+                "classifier": null,
+                "extension": "jar",
+            }),
+            json!({ "type": "groups", "kind": "root", "groups": ["com", "org"] }),
+            json!({
+                "type": "error",
+                "error": "unrecognised doc type in record 3 (byte 150)",
+                "raw": [["n", "lonely"]],
+            }),
+        ],
+        events
+    );
+
+    for event in &events[..3] {
+        let again: Event = serde_json::from_value(event.clone())?;
+        assert_eq!(*event, serde_json::to_value(again)?);
+    }
+    assert!(serde_json::from_value::<Event>(events[3].clone()).is_err());
+    Ok(())
+}
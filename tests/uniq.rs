@@ -0,0 +1,80 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
+use nexers::nexus::UniqId;
+
+use common::TINY;
+
+#[test]
+fn round_trip_tiny() -> Result<()> {
+    let mut reader = IndexReader::new(TINY)?;
+    let mut seen = 0;
+    while let Some(record) = reader.next_record()? {
+        let u = record.get("u")?.expect("only docs in the sample");
+        let id = match record.to_event()? {
+            Event::Doc(doc) => doc.id,
+            other => panic!("unexpected: {other:?}"),
+        };
+
+        assert_eq!(u, format!("{id:#}"));
+        assert_eq!(id, u.parse::<UniqId>()?);
+        assert_eq!(id, id.to_string().parse::<UniqId>()?);
+        seen += 1;
+    }
+    assert_eq!(2, seen);
+    Ok(())
+}
+
+#[test]
+fn colons() -> Result<()> {
+    let id = UniqId {
+        group: "org.example".into(),
+        artifact: "example".into(),
+        version: "1.0".into(),
+        classifier: None,
+        extension: None,
+    };
+    assert_eq!("org.example:example:1.0", id.to_string());
+    assert_eq!("org.example|example|1.0|NA", format!("{id:#}"));
+    assert_eq!(id, "org.example:example:1.0".parse()?);
+    assert_eq!(id, "org.example|example|1.0|NA".parse()?);
+
+    let id = UniqId {
+        classifier: Some("sources".into()),
+        extension: Some("jar".into()),
+        ..id
+    };
+    assert_eq!("org.example:example:1.0:sources@jar", id.to_string());
+    assert_eq!("org.example|example|1.0|sources|jar", format!("{id:#}"));
+    assert_eq!(id, "org.example:example:1.0:sources@jar".parse()?);
+    assert_eq!(id, "org.example|example|1.0|sources|jar".parse()?);
+
+    let id = UniqId {
+        classifier: None,
+        extension: Some("pom".into()),
+        ..id
+    };
+    assert_eq!("org.example:example:1.0@pom", id.to_string());
+    assert_eq!("org.example|example|1.0|NA|pom", format!("{id:#}"));
+    assert_eq!(id, "org.example:example:1.0@pom".parse()?);
+    Ok(())
+}
+
+#[test]
+fn bad() {
+    for value in [
+        "",
+        "org.example",
+        "org.example:example",
+        "org.example::1.0",
+        "org.example:example:1.0:",
+        "org.example:example:1.0@",
+        "org.example:example:1.0:sources:jar:extra",
+        "org.example|example",
+    ] {
+        assert!(value.parse::<UniqId>().is_err(), "{value:?}");
+    }
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use anyhow::Result;
+
+use nexers::version::MavenVersion;
+use nexers::version::VersionKind;
+use nexers::version::VersionRange;
+
+use common::docs;
+
+fn parse(versions: &[&str]) -> Vec<MavenVersion> {
+    versions.iter().map(|v| MavenVersion::parse(v)).collect()
+}
+
+fn check_ordered(versions: &[&str]) {
+    let versions = parse(versions);
+    for (i, low) in versions.iter().enumerate() {
+        for high in &versions[i + 1..] {
+            assert!(low < high, "{low} < {high}");
+            assert!(high > low, "{high} > {low}");
+        }
+    }
+}
+
+fn check_equal(versions: &[&str]) {
+    let versions = parse(versions);
+    for left in &versions {
+        for right in &versions {
+            assert_eq!(left, right, "{left} == {right}");
+        }
+    }
+}
+
+// from maven's ComparableVersionTest
+#[test]
+fn qualifiers() {
+    check_ordered(&[
+        "1-alpha2snapshot",
+        "1-alpha2",
+        "1-alpha-123",
+        "1-beta-2",
+        "1-beta123",
+        "1-m2",
+        "1-m11",
+        "1-rc",
+        "1-cr2",
+        "1-rc123",
+        "1-SNAPSHOT",
+        "1",
+        "1-sp",
+        "1-sp2",
+        "1-sp123",
+        "1-abc",
+        "1-def",
+        "1-pom-1",
+        "1-1-snapshot",
+        "1-1",
+        "1-2",
+        "1-123",
+    ]);
+}
+
+#[test]
+fn numbers() {
+    check_ordered(&[
+        "2.0-1",
+        "2.0.1",
+        "2.0.1-klm",
+        "2.0.1-lmn",
+        "2.0.1-xyz",
+        "2.0.1-123",
+    ]);
+    check_ordered(&[
+        "2.0", "2-1", "2.0.a", "2.0.0.a", "2.0.2", "2.0.123", "2.1.0", "2.1-a", "2.1b", "2.1-c",
+        "2.1-1", "2.1.0.1", "2.2", "2.123", "11.a2", "11.a11", "11.b2", "11.b11", "11.m2",
+        "11.m11", "11", "11.a", "11b", "11c", "11m",
+    ]);
+    check_ordered(&[
+        "1",
+        "1.1",
+        "1.2",
+        "1.10",
+        "2",
+        "10",
+        "100000000000000000000",
+    ]);
+    check_ordered(&["1.0-alpha-1", "1.0", "1.0-1"]);
+    check_ordered(&["1.0-SNAPSHOT", "1.0", "1.0.1-SNAPSHOT", "1.0.1"]);
+}
+
+#[test]
+fn equality() {
+    check_equal(&[
+        "1",
+        "1.0",
+        "1.0.0",
+        "1-0",
+        "1.0-0",
+        "1.0.0-0",
+        "1-ga",
+        "1.0-final",
+    ]);
+    check_equal(&["1a", "1-a", "1.0-a", "1.0.0-a"]);
+    check_equal(&["1x", "1-x", "1.0-x", "1.0.0-x"]);
+    check_equal(&["1ga", "1GA", "1-ga", "1.0", "1.0.RELEASE"]);
+    check_equal(&["1cr", "1rc", "1-cr", "1-rc"]);
+    check_equal(&["1a1", "1-a1", "1-alpha-1", "1.0-alpha1", "1.0-ALPHA-1"]);
+    check_equal(&["1b2", "1-b2", "1-beta-2", "1.0-beta2"]);
+    check_equal(&["1m3", "1-m3", "1-milestone-3", "1.0-MILESTONE3"]);
+    check_equal(&["010", "10"]);
+}
+
+#[test]
+fn sorting() {
+    let mut versions = parse(&["1.10", "1.2-SNAPSHOT", "1.2", "1.2-rc1", "1.9"]);
+    versions.sort();
+    let versions = versions.iter().map(|v| v.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        vec!["1.2-rc1", "1.2-SNAPSHOT", "1.2", "1.9", "1.10"],
+        versions
+    );
+}
+
+#[test]
+fn text() {
+    let version = MavenVersion::parse("1.0-CR-1");
+    assert_eq!("1.0-CR-1", version.to_string());
+    assert_eq!("1-rc-1", version.canonical());
+}
+
+#[test]
+fn kinds() {
+    for (version, kind) in [
+        ("1.0", VersionKind::Release),
+        ("1.0-final", VersionKind::Release),
+        ("1.0-sp1", VersionKind::Release),
+        ("31.1-jre", VersionKind::Release),
+        ("1.0-alpha-1", VersionKind::PreRelease),
+        ("1.0b2", VersionKind::PreRelease),
+        ("1.0-M3", VersionKind::PreRelease),
+        ("1.0-RC1", VersionKind::PreRelease),
+        ("1.0-cr", VersionKind::PreRelease),
+        ("1.0-SNAPSHOT", VersionKind::Snapshot),
+        ("1.0-rc1-SNAPSHOT", VersionKind::Snapshot),
+        ("1.0-20240101.123456-1", VersionKind::Snapshot),
+    ] {
+        assert_eq!(kind, MavenVersion::parse(version).kind(), "{version}");
+    }
+
+    assert!(MavenVersion::parse("2").is_release());
+    assert!(MavenVersion::parse("2-beta").is_pre_release());
+    assert!(MavenVersion::parse("2-SNAPSHOT").is_snapshot());
+}
+
+#[test]
+fn ranges() -> Result<()> {
+    for (spec, inside, outside) in [
+        (
+            "[1.0,2.0)",
+            &["1.0", "1.5", "2.0-SNAPSHOT"][..],
+            &["0.9", "2.0", "1.0-rc1"][..],
+        ),
+        ("(,1.5]", &["0.1", "1.5", "1.5-ga"], &["1.5.1", "1.5-sp1"]),
+        ("[1.2]", &["1.2", "1.2.0"], &["1.2.1", "1.1"]),
+        ("(1.0,)", &["1.0.1", "1.0-sp"], &["1.0", "1.0-alpha"]),
+        (
+            "(,1.0],[1.2,)",
+            &["0.9", "1.0", "1.2", "3"],
+            &["1.1", "1.0.1"],
+        ),
+        ("[1.0,1.1), [2.0,2.1)", &["1.0.5", "2.0"], &["1.1", "2.1"]),
+        ("1.0", &["0.1", "1.0", "9"], &[]),
+    ] {
+        let range = VersionRange::parse(spec)?;
+        for version in inside {
+            assert!(range.matches(version), "{version} in {spec}");
+        }
+        for version in outside {
+            assert!(!range.matches(version), "{version} not in {spec}");
+        }
+    }
+
+    assert_eq!(
+        Some(&MavenVersion::parse("1.0")),
+        VersionRange::parse("1.0")?.recommended()
+    );
+    assert_eq!(None, VersionRange::parse("[1.0]")?.recommended());
+    Ok(())
+}
+
+#[test]
+fn bad_ranges() {
+    for spec in [
+        "",
+        "[1.0",
+        "(1.0)",
+        "[]",
+        "[2.0,1.0]",
+        "[1.0,2.0],[1.5,3.0]",
+        "[1.0,),[2.0,3.0]",
+        "[1.0,2.0],1.5",
+        "[1.0,2.0,3.0]",
+    ] {
+        assert!(VersionRange::parse(spec).is_err(), "{spec:?}");
+    }
+}
+
+#[test]
+fn range_text() -> Result<()> {
+    for spec in [
+        "[1.0,2.0)",
+        "(,1.5]",
+        "[1.2]",
+        "(1.0,)",
+        "(,1.0],[1.2,)",
+        "1.0",
+    ] {
+        assert_eq!(spec, spec.parse::<VersionRange>()?.to_string());
+    }
+    assert_eq!(
+        "[1.0,1.1),[2.0,2.1)",
+        VersionRange::parse(" [1.0 , 1.1) , [2.0,2.1) ")?.to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn filtering() -> Result<()> {
+    let range = VersionRange::parse("[1.0-alpha-2,)")?;
+
+    let versions = vec!["1.0-alpha-1".to_string(), "1.0-alpha-2".to_string()];
+    assert_eq!(
+        vec!["1.0-alpha-2"],
+        range.filter(versions).collect::<Vec<_>>()
+    );
+
+    let docs = docs();
+    let found = range
+        .filter_docs(&docs)
+        .map(|doc| doc.id.version.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["1.0-alpha-2"], found);
+    Ok(())
+}
@@ -3,6 +3,7 @@ use std::io;
 use anyhow::Result;
 
 use nexers::nexus::Event;
+use nexers::nexus::IndexReader;
 
 #[test]
 fn load() -> Result<()> {
@@ -43,6 +44,23 @@ fn load() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn iterate() -> Result<()> {
+    let reader = IndexReader::new(&include_bytes!("tiny-file")[..])?;
+    assert_eq!(1, reader.version());
+    assert_eq!(1_545_614_688_784, reader.timestamp_ms());
+
+    let versions = reader
+        .map(|ev| match ev? {
+            Event::Doc(d) => Ok(d.id.version),
+            other => panic!("unexpected event: {other:?}"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    assert_eq!(vec!["1.0-alpha-2", "1.0-alpha-1"], versions);
+    Ok(())
+}
+
 #[cfg(never)]
 fn print() {
     for (e, fields) in &errors {
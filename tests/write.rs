@@ -0,0 +1,131 @@
+mod common;
+
+use std::io;
+
+use anyhow::Result;
+
+use nexers::nexus::AttachmentStatus;
+use nexers::nexus::Doc;
+use nexers::nexus::Event;
+use nexers::nexus::FullInfo;
+use nexers::nexus::GroupKind;
+use nexers::nexus::Osgi;
+use nexers::nexus::Plugin;
+use nexers::nexus::UniqId;
+use nexers::nexus::Writer;
+
+use common::only_docs;
+use common::TINY;
+
+fn read_all(from: &[u8]) -> Result<Vec<Event>> {
+    let mut events = Vec::new();
+    nexers::nexus::read(io::BufReader::new(from), |ev| {
+        events.push(ev);
+        Ok(())
+    })?;
+    Ok(events)
+}
+
+#[test]
+fn tiny_file_is_reproduced() -> Result<()> {
+    let timestamp_ms = i64::from_be_bytes(TINY[1..9].try_into()?);
+    let events = read_all(TINY)?;
+
+    let mut writer = Writer::new(Vec::new(), timestamp_ms)?;
+    for event in &events {
+        writer.write(event)?;
+    }
+    let written = writer.into_inner()?;
+
+    assert_eq!(TINY, written.as_slice());
+    assert_eq!(only_docs(events), only_docs(read_all(&written)?));
+    Ok(())
+}
+
+#[test]
+fn round_trip() -> Result<()> {
+    let doc = Doc {
+        id: UniqId {
+            group: "com.example".into(),
+            artifact: "nul\0and\u{1f980}".into(),
+            version: "1.0".into(),
+            classifier: Some("sources".into()),
+            extension: Some("jar".into()),
+        },
+        object_info: FullInfo {
+            packaging: "bundle".into(),
+            last_modified: 1_318_434_018_114,
+            size: None,
+            source_attached: AttachmentStatus::Present,
+            javadoc_attached: AttachmentStatus::Unavailable,
+            signature_attached: AttachmentStatus::Absent,
+            extension: "jar".into(),
+        },
+        modified: 1_318_434_018_187,
+        name: Some("Crab \u{1f980}".to_string()),
+        description: None,
+        checksum: Some([7; 20]),
+        class_names: Some(vec!["/com/example/Crab".to_string()]),
+        plugin: Some(Plugin {
+            prefix: Some("crab".to_string()),
+            goals: vec!["pinch".to_string(), "scuttle".to_string()],
+        }),
+        osgi: Some(Osgi {
+            symbolic_name: Some("com.example.crab".to_string()),
+            export_package: Some(
+                r#"com.example.crab;version="1.0",com.example.shell;uses:="a,b""#.to_string(),
+            ),
+            ..Osgi::default()
+        }),
+        extra: [("sha256".into(), "abc".to_string())].into_iter().collect(),
+    };
+
+    assert_eq!(
+        vec!["com.example.crab", "com.example.shell"],
+        doc.osgi.as_ref().expect("set").exported_packages()
+    );
+
+    let mut writer = Writer::new(Vec::new(), -1)?;
+    writer.write_descriptor("1.0", "central")?;
+    writer.write_groups(GroupKind::Root, &["com"])?;
+    writer.write_groups(GroupKind::All, &["com.example"])?;
+    writer.write_doc(&doc)?;
+    writer.write_delete(&doc.id)?;
+    let written = writer.into_inner()?;
+
+    let mut events = read_all(&written)?.into_iter();
+    match events.next() {
+        Some(Event::Descriptor {
+            version,
+            repository_id,
+        }) => assert_eq!(
+            ("1.0", "central"),
+            (version.as_str(), repository_id.as_str())
+        ),
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match events.next() {
+        Some(Event::Groups { kind, groups }) => {
+            assert_eq!(GroupKind::Root, kind);
+            assert_eq!(vec!["com"], groups);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match events.next() {
+        Some(Event::Groups { kind, groups }) => {
+            assert_eq!(GroupKind::All, kind);
+            assert_eq!(vec!["com.example"], groups);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match events.next() {
+        Some(Event::Doc(d)) => assert_eq!(doc, d),
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match events.next() {
+        Some(Event::Delete(id)) => assert_eq!(doc.id, id),
+        other => panic!("unexpected event: {other:?}"),
+    }
+    assert!(events.next().is_none());
+    Ok(())
+}